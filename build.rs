@@ -1,6 +1,7 @@
 #[macro_use]
 extern crate clap;
 
+#[allow(dead_code)]
 mod module { include!("src/module.rs"); }
 mod cli { include!("src/cli.rs"); }
 
@@ -0,0 +1,43 @@
+# Transient prompt for bash: once a command is submitted, the prompt it was
+# typed at is redrawn using `powerline-rs --transient`, keeping the
+# scrollback tidy. Source this after setting up your normal prompt, and it
+# uses the same arguments if that was done with `powerline-rs init bash`.
+# Needs bash 4.4 or later for PS0.
+#
+# Readline has no hook for when a line is accepted, so Enter is rebound to
+# first save the line with `bind -x` and then accept it. That's the line as
+# typed, unlike `history 1`, which HISTCONTROL may have skipped. PS0 is
+# printed right after readline returns and before the command runs, and
+# moves the cursor back up over the full prompt and the command, counting
+# lines that wrap around the edge of the terminal, to print them again in
+# the minimal form.
+
+__powerline_transient_save() {
+    __powerline_transient_line="$READLINE_LINE"
+}
+
+# Counts the terminal rows some text takes up
+__powerline_transient_rows() {
+    local line rows=0
+    while IFS= read -r line; do
+        rows=$(( rows + (${#line} ? (${#line} - 1) / COLUMNS + 1 : 1) ))
+    done <<< "$1"
+    echo "$rows"
+}
+
+__powerline_transient() {
+    # PS0 sees the exit status the prompt was drawn with
+    local exit_code=$? prompt
+    # Expand the prompt the way bash draws it and keep only what's visible,
+    # dropping colors, OSC sequences and the markers \[ \] turn into. The x
+    # stops a trailing newline from being stripped.
+    prompt="$(printf '%sx' "${PS1@P}" | sed -E -e $'s/\e\\[[0-9;]*m//g' -e $'s/\e\\][^\a\e]*(\a|\e\\\\)//g' -e $'s/[\001\002]//g')"
+    prompt="${prompt%x}"
+
+    printf '\e[%dA\r\e[J' "$(__powerline_transient_rows "$prompt$__powerline_transient_line")"
+    printf '%s%s\n' "$("${__powerline_command[@]:-powerline-rs}" --shell bare --transient "$exit_code")" "$__powerline_transient_line"
+}
+
+bind -x '"\C-x\C-p": __powerline_transient_save'
+bind '"\C-m": "\C-x\C-p\C-j"'
+PS0='$(__powerline_transient)'
@@ -0,0 +1,155 @@
+use std::{
+    env,
+    fs::OpenOptions,
+    io::{Read, Write},
+    mem,
+    os::unix::io::AsRawFd,
+    time::{Duration, Instant}
+};
+
+/// How long to wait for the terminal to answer the OSC 11 query. Terminals
+/// that don't support it never answer, so keep this short. It's only paid
+/// once per shell, by `init`.
+const QUERY_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Background {
+    Light,
+    Dark
+}
+
+/// Figures out the terminal background from the explicit
+/// $POWERLINE_RS_BACKGROUND, then $COLORFGBG. This runs on every prompt, so
+/// it never asks the terminal; `init` does that once with `query` and
+/// exports the answer instead.
+pub fn detect() -> Option<Background> {
+    if let Ok(value) = env::var("POWERLINE_RS_BACKGROUND") {
+        match &*value {
+            "light" => return Some(Background::Light),
+            "dark" => return Some(Background::Dark),
+            _ => ()
+        }
+    }
+    env::var("COLORFGBG").ok().and_then(|value| from_colorfgbg(&value))
+}
+
+/// Asks the terminal for its background color with OSC 11, but only when
+/// the shell is interactive.
+pub fn query() -> Option<Background> {
+    if unsafe { libc::isatty(libc::STDIN_FILENO) } != 1 {
+        return None;
+    }
+    query_terminal()
+}
+
+impl Background {
+    pub fn name(self) -> &'static str {
+        match self {
+            Background::Light => "light",
+            Background::Dark => "dark"
+        }
+    }
+}
+
+/// Parses $COLORFGBG, which is "fg;bg" or "fg;default;bg" where bg is one of
+/// the 16 ANSI colors.
+fn from_colorfgbg(value: &str) -> Option<Background> {
+    match value.rsplit(';').next()?.parse::<u8>().ok()? {
+        0..=6 | 8 => Some(Background::Dark),
+        7 | 9..=15 => Some(Background::Light),
+        _ => None
+    }
+}
+
+/// Parses an OSC 11 reply such as "\x1b]11;rgb:ffff/ffff/ffff\x07".
+fn from_osc11_reply(reply: &str) -> Option<Background> {
+    let start = reply.find("rgb:")? + 4;
+    let rgb = reply[start..].trim_end_matches(&['\x07', '\x1b', '\\'][..]);
+
+    let mut channels = rgb.split('/').map(|hex| {
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        let max = (1u32 << (4 * hex.len().min(4) as u32)) - 1;
+        Some(value as f32 / max as f32)
+    });
+    let r = channels.next()??;
+    let g = channels.next()??;
+    let b = channels.next()??;
+
+    if 0.2126 * r + 0.7152 * g + 0.0722 * b > 0.5 {
+        Some(Background::Light)
+    } else {
+        Some(Background::Dark)
+    }
+}
+
+fn query_terminal() -> Option<Background> {
+    let mut tty = OpenOptions::new().read(true).write(true).open("/dev/tty").ok()?;
+    let fd = tty.as_raw_fd();
+
+    // Disable line buffering and echo so we can read the reply ourselves
+    let mut old: libc::termios = unsafe { mem::zeroed() };
+    if unsafe { libc::tcgetattr(fd, &mut old) } != 0 {
+        return None;
+    }
+    let mut raw = old;
+    raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+    raw.c_cc[libc::VMIN] = 0;
+    raw.c_cc[libc::VTIME] = 0;
+    if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+        return None;
+    }
+
+    let mut reply = Vec::new();
+    if tty.write_all(b"\x1b]11;?\x07").and_then(|()| tty.flush()).is_ok() {
+        let deadline = Instant::now() + QUERY_TIMEOUT;
+        // Read a byte at a time, so nothing typed after the reply is eaten
+        let mut byte = [0; 1];
+
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            let mut pollfd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+            if left == Duration::from_secs(0)
+                    || unsafe { libc::poll(&mut pollfd, 1, left.as_millis() as libc::c_int) } <= 0 {
+                break;
+            }
+            match tty.read(&mut byte) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => reply.push(byte[0])
+            }
+            // Replies end with either BEL or ST (ESC \)
+            if reply.ends_with(b"\x07") || reply.ends_with(b"\x1b\\") {
+                break;
+            }
+        }
+    }
+
+    unsafe { libc::tcsetattr(fd, libc::TCSANOW, &old); }
+
+    from_osc11_reply(&String::from_utf8_lossy(&reply))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colorfgbg() {
+        assert_eq!(from_colorfgbg("15;0"), Some(Background::Dark));
+        assert_eq!(from_colorfgbg("0;15"), Some(Background::Light));
+        assert_eq!(from_colorfgbg("0;default;7"), Some(Background::Light));
+        assert_eq!(from_colorfgbg("7;8"), Some(Background::Dark));
+        assert_eq!(from_colorfgbg("15;default"), None);
+        assert_eq!(from_colorfgbg(""), None);
+    }
+
+    #[test]
+    fn osc11_reply() {
+        assert_eq!(from_osc11_reply("\x1b]11;rgb:ffff/ffff/ffff\x07"), Some(Background::Light));
+        assert_eq!(from_osc11_reply("\x1b]11;rgb:0000/2b2b/3636\x1b\\"), Some(Background::Dark));
+        // Some terminals only send 8 bits per channel
+        assert_eq!(from_osc11_reply("\x1b]11;rgb:fd/f6/e3\x07"), Some(Background::Light));
+        assert_eq!(from_osc11_reply("\x1b]11;rgb:ff/00/00\x07"), Some(Background::Dark));
+        assert_eq!(from_osc11_reply("\x1b]11;rgb:ffff/ffff\x07"), None);
+        assert_eq!(from_osc11_reply(""), None);
+    }
+}
@@ -1,6 +1,4 @@
-use clap::{App, Arg};
-
-pub const TIME_FORMAT_DEFAULT: &str = "%I:%M %p";
+use clap::{App, AppSettings, Arg, SubCommand};
 
 pub fn build_cli() -> App<'static, 'static> {
     App::new(crate_name!())
@@ -24,11 +22,95 @@ pub fn build_cli() -> App<'static, 'static> {
                 .value_name("int")
                 .default_value("15")
         )
+        .arg(
+            Arg::with_name("duration")
+                .long("duration")
+                .help("How long the previous command ran, in milliseconds")
+                .takes_value(true)
+                .value_name("ms")
+                .conflicts_with("duration-start")
+        )
+        .arg(
+            Arg::with_name("duration-start")
+                .long("duration-start")
+                .help("When the previous command started, in seconds since the epoch (like $EPOCHREALTIME)")
+                .takes_value(true)
+                .value_name("timestamp")
+        )
+        .arg(
+            Arg::with_name("duration-end")
+                .long("duration-end")
+                .help("When the previous command ended, defaults to now")
+                .takes_value(true)
+                .value_name("timestamp")
+                .requires("duration-start")
+        )
+        .arg(
+            Arg::with_name("duration-min")
+                .long("duration-min")
+                .help("Only show the duration if the command took at least this many milliseconds")
+                .takes_value(true)
+                .value_name("ms")
+                .default_value("2000")
+        )
+        .arg(
+            Arg::with_name("battery-threshold")
+                .long("battery-threshold")
+                .help("Hide the battery module above this percentage")
+                .takes_value(true)
+                .value_name("int")
+                .default_value("100")
+        )
+        .arg(
+            Arg::with_name("load-threshold")
+                .long("load-threshold")
+                .help("Hide the load module below this percentage of the CPU count")
+                .takes_value(true)
+                .value_name("int")
+                .default_value("0")
+        )
+        .arg(
+            Arg::with_name("mem-threshold")
+                .long("mem-threshold")
+                .help("Hide the mem module below this percentage of memory used")
+                .takes_value(true)
+                .value_name("int")
+                .default_value("0")
+        )
+        .arg(
+            Arg::with_name("disk-threshold")
+                .long("disk-threshold")
+                .help("Hide the disk module below this percentage of the filesystem used")
+                .takes_value(true)
+                .value_name("int")
+                .default_value("0")
+        )
+        .arg(
+            Arg::with_name("jobs")
+                .long("jobs")
+                .help("The number of background jobs, for shells where the jobs module can't ask the shell itself")
+                .takes_value(true)
+                .value_name("int")
+        )
         .arg(
             Arg::with_name("error")
                 .help("Exit code of previously executed command")
                 .default_value("0")
         )
+        .arg(
+            Arg::with_name("pipestatus")
+                .long("pipestatus")
+                .help("Exit codes of every command in the previous pipeline, separated by ','. \
+                       Overrides the single exit code when set.")
+                .takes_value(true)
+                .value_name("int")
+                .value_delimiter(",")
+        )
+        .arg(
+            Arg::with_name("error-verbose")
+                .long("error-verbose")
+                .help("Show the exit code number along with its name, like 130 INT")
+        )
         .arg(
             Arg::with_name("time_format")
                 .long("time_format")
@@ -36,7 +118,7 @@ pub fn build_cli() -> App<'static, 'static> {
                 .hidden(!cfg!(feature = "chrono"))
                 .takes_value(true)
                 .value_name("string")
-                .default_value(TIME_FORMAT_DEFAULT)
+                .default_value(crate::module::TIME_FORMAT_DEFAULT)
         )
         .arg(
             Arg::with_name("modules")
@@ -48,10 +130,25 @@ pub fn build_cli() -> App<'static, 'static> {
                 .value_delimiter(",")
                 .default_value("user,host,ssh,cwd,perms,git,gitstage,virtualenv,root")
         )
+        .arg(
+            Arg::with_name("transient")
+                .long("transient")
+                .help("Print the minimal prompt that replaces old prompts in the scrollback, using --transient-modules")
+        )
+        .arg(
+            Arg::with_name("transient-modules")
+                .long("transient-modules")
+                .help("The list of modules to load for --transient, separated by ','")
+                .takes_value(true)
+                .value_name("string")
+                .possible_values(crate::module::ALL)
+                .value_delimiter(",")
+                .default_value("time,prompt")
+        )
         .arg(
             Arg::with_name("newline")
                 .long("newline")
-                .help("Adds a newline after the prompt")
+                .help("Adds a newline after the prompt, except with --transient")
         )
         .arg(
             Arg::with_name("shell")
@@ -69,9 +166,119 @@ pub fn build_cli() -> App<'static, 'static> {
                 .takes_value(true)
                 .value_name("file")
         )
+        .arg(
+            Arg::with_name("theme-light")
+                .long("theme-light")
+                .help("The theme to use when the terminal has a light background")
+                .takes_value(true)
+                .value_name("file")
+        )
+        .arg(
+            Arg::with_name("theme-dark")
+                .long("theme-dark")
+                .help("The theme to use when the terminal has a dark background. \
+                       The background is read from $POWERLINE_RS_BACKGROUND (light or dark), \
+                       or $COLORFGBG. The init scripts set $POWERLINE_RS_BACKGROUND by asking the terminal.")
+                .takes_value(true)
+                .value_name("file")
+        )
+        .arg(
+            Arg::with_name("semantic-prompt")
+                .long("semantic-prompt")
+                .help("Mark the prompt with OSC 133 sequences, letting terminals jump between prompts and select command output")
+        )
+        .arg(
+            Arg::with_name("title")
+                .long("title")
+                .help("Set the terminal window title, expanding {user}, {host}, {cwd} and {branch}")
+                .takes_value(true)
+                .value_name("template")
+        )
+        .arg(
+            Arg::with_name("hyperlinks")
+                .long("hyperlinks")
+                .help("Make the cwd and git branch clickable, using OSC 8 hyperlinks")
+        )
+        .arg(
+            Arg::with_name("default-user")
+                .long("default-user")
+                .help("Hide the user and host modules for this user, unless over ssh, after switching users or as root")
+                .takes_value(true)
+                .value_name("user")
+        )
+        .arg(
+            Arg::with_name("user-host-only")
+                .long("user-host-only")
+                .help("Only show the user and host modules in these situations, and always for root")
+                .takes_value(true)
+                .value_name("string")
+                .possible_values(&["ssh", "switched"])
+                .value_delimiter(",")
+        )
+        .arg(
+            Arg::with_name("user-host-combined")
+                .long("user-host-combined")
+                .help("Make the user module show user@host, and the host module nothing")
+        )
+        .arg(
+            Arg::with_name("ssh-client-ip")
+                .long("ssh-client-ip")
+                .help("Make the ssh module show the address connected from")
+        )
+        .arg(
+            Arg::with_name("toolchain-active")
+                .long("toolchain-active")
+                .help("Make the rust, node, python and go modules also show the version in $PATH")
+        )
+        .arg(
+            Arg::with_name("git-link-template")
+                .long("git-link-template")
+                .help("The URL the git branch links to, built from the remote's {host} and {path} and the {branch}")
+                .takes_value(true)
+                .value_name("string")
+                .default_value(crate::module::GIT_LINK_TEMPLATE_DEFAULT)
+        )
         .arg(
             Arg::with_name("rtl")
                 .long("rtl")
                 .help("Print everything from right to left")
             )
+        .subcommand(
+            SubCommand::with_name("init")
+                .about("Print the script that sets up the prompt for a shell")
+                .arg(
+                    Arg::with_name("shell")
+                        .help("The shell to print the script for")
+                        .required(true)
+                        .possible_values(&["bash", "zsh", "fish", "ion"])
+                )
+                .arg(
+                    Arg::with_name("args")
+                        .help("Extra arguments to pass to powerline-rs, like --modules")
+                        .multiple(true)
+                        .last(true)
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("theme")
+                .about("Inspect a theme")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("preview")
+                        .about("Render every segment type with fake data")
+                        .arg(
+                            Arg::with_name("file")
+                                .help("The theme file to preview")
+                                .required(true)
+                        )
+                )
+                .subcommand(
+                    SubCommand::with_name("dump")
+                        .about("Print the effective theme, merged with the defaults")
+                        .arg(
+                            Arg::with_name("file")
+                                .help("The theme file to dump, defaults to the one passed with --theme")
+                        )
+                )
+        )
 }
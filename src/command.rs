@@ -0,0 +1,134 @@
+use std::{
+    io::{Read, Write},
+    os::unix::process::CommandExt,
+    process::{Child, Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant}
+};
+
+/// How long a command may run unless configured otherwise
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A command started with `spawn`, whose output hasn't been collected yet
+pub struct Pending {
+    child: Child,
+    deadline: Instant,
+    rx: mpsc::Receiver<String>
+}
+
+/// Starts a command with `sh -c`, optionally writing some input to it. The
+/// output is read in the background so that several commands can run at the
+/// same time.
+pub fn spawn(command: &str, input: Option<String>, timeout: Duration) -> Option<Pending> {
+    let mut sh = Command::new("sh");
+    sh
+        .arg("-c")
+        .arg(command)
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    // Put the command in its own process group, so a timeout can kill
+    // everything it started and not just the shell
+    unsafe {
+        sh.pre_exec(|| {
+            libc::setpgid(0, 0);
+            Ok(())
+        });
+    }
+    let mut child = sh.spawn().ok()?;
+
+    let stdin = child.stdin.take();
+    let mut stdout = child.stdout.take().unwrap();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        if let (Some(mut stdin), Some(input)) = (stdin, input) {
+            // A command that doesn't read its input closes the pipe early,
+            // which is no reason to throw away what it printed. Dropping
+            // stdin closes it, so the command knows we're done.
+            stdin.write_all(input.as_bytes()).ok();
+        }
+        let mut output = String::new();
+        if stdout.read_to_string(&mut output).is_ok() {
+            tx.send(output).ok();
+        }
+    });
+
+    Some(Pending {
+        child,
+        deadline: Instant::now() + timeout,
+        rx
+    })
+}
+
+impl Pending {
+    /// Waits for the output of a successful command. Returns None and kills
+    /// the command if it fails or doesn't finish in time.
+    pub fn wait(mut self) -> Option<String> {
+        let output = self.rx.recv_timeout(self.deadline.saturating_duration_since(Instant::now())).ok();
+
+        // The output is complete once stdout is closed, but the command may
+        // still be running
+        if output.is_some() {
+            loop {
+                match self.child.try_wait() {
+                    Ok(Some(status)) => return output.filter(|_| status.success()),
+                    Ok(None) if Instant::now() < self.deadline => thread::sleep(Duration::from_millis(1)),
+                    _ => break
+                }
+            }
+        }
+
+        self.kill();
+        None
+    }
+
+    fn kill(&mut self) {
+        // The child isn't reaped until the wait below, so its pid (and with
+        // it the process group id) can't have been reused yet
+        unsafe { libc::kill(-(self.child.id() as libc::pid_t), libc::SIGKILL); }
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn run(command: &str, input: Option<&str>, timeout: u64) -> Option<String> {
+        spawn(command, input.map(String::from), Duration::from_millis(timeout))?.wait()
+    }
+
+    #[test]
+    fn output() {
+        assert_eq!(run("echo hi", None, 1000), Some(String::from("hi\n")));
+        assert_eq!(run("tr a-z A-Z", Some("hi"), 1000), Some(String::from("HI")));
+    }
+
+    #[test]
+    fn input_ignored() {
+        // More than fits in the pipe, so the write fails once echo exits
+        let input = "x".repeat(1 << 20);
+        assert_eq!(run("echo hi", Some(&input), 1000), Some(String::from("hi\n")));
+    }
+
+    #[test]
+    fn failure() {
+        assert_eq!(run("echo hi; exit 1", None, 1000), None);
+    }
+
+    #[test]
+    fn timeout_kills_pipeline() {
+        let dir = TempDir::new("command");
+        let marker = dir.join("marker");
+        let command = format!("(sleep 0.3; touch '{}') | cat", marker.display());
+        assert_eq!(run(&command, None, 50), None);
+
+        // Without the process group only `sh` would be killed, and the
+        // subshell would live on to create the file
+        thread::sleep(Duration::from_millis(500));
+        assert!(!marker.exists());
+    }
+}
@@ -24,13 +24,45 @@ pub fn sgr_code(shell: Shell, code: &str) -> String {
     }
 }
 
-// pub fn root(shell: Shell) -> &'static str {
-//     match shell {
-//         Shell::Bare => "$",
-//         Shell::Bash => "\\$",
-//         Shell::Zsh  => "%#"
-//     }
-// }
+/// Wraps an OSC (operating system command) sequence so the shell doesn't
+/// count it towards the prompt width.
+pub fn osc(shell: Shell, code: &str) -> String {
+    match shell {
+        Shell::Bare => format!("\x1b]{}\x07", code),
+        Shell::Bash => format!("\\[\\e]{}\\a\\]", code),
+        Shell::Zsh  => format!("%{{\x1b]{}\x07%}}", code)
+    }
+}
+
+/// Percent-encodes everything but unreserved characters and '/', for
+/// putting paths into URLs.
+pub fn percent_encode(s: &str) -> String {
+    let mut output = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => output.push(byte as char),
+            _ => output.push_str(&format!("%{:02X}", byte))
+        }
+    }
+    output
+}
+
+/// Returns a shell escape that shows `root` for the superuser and `user`
+/// for anyone else, if the shell has one.
+pub fn root(shell: Shell, user: char, root: char) -> Option<String> {
+    match shell {
+        Shell::Bare => None,
+        Shell::Bash if user == '$' && root == '#' => Some(String::from("\\$")),
+        Shell::Bash => None,
+        Shell::Zsh => {
+            let separator = ['.', '/', '|', ':'].iter().find(|&&c| c != user && c != root)?;
+            let (mut user, mut root) = (user.to_string(), root.to_string());
+            escape(shell, &mut user);
+            escape(shell, &mut root);
+            Some(format!("%(!{sep}{}{sep}{})", root, user, sep = separator))
+        }
+    }
+}
 
 pub fn escape(shell: Shell, string: &mut String) {
     if shell == Shell::Bare {
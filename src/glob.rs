@@ -0,0 +1,86 @@
+/// Matches text against a pattern where '*' matches any (possibly empty)
+/// sequence of characters and '?' matches exactly one. There's no escaping,
+/// since none of the things we match against are expected to contain a
+/// literal '*' or '?'.
+pub fn matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // The last '*' seen and where in the text it's matched up to, to retry
+    // from with one more character when the rest doesn't match
+    let mut star = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            },
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            },
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                },
+                None => return false
+            }
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Matches text against a comma separated list of patterns
+pub fn matches_any(patterns: &str, text: &str) -> bool {
+    patterns.split(',').any(|pattern| matches(pattern.trim(), text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literal() {
+        assert!(matches("prod", "prod"));
+        assert!(!matches("prod", "production"));
+        assert!(!matches("prod", "pro"));
+    }
+
+    #[test]
+    fn star() {
+        assert!(matches("prod*", "prod"));
+        assert!(matches("prod*", "production"));
+        assert!(matches("*-prod", "eu-prod"));
+        assert!(!matches("*-prod", "eu-prod-2"));
+        assert!(matches("gke_*_prod*", "gke_project_prod-1"));
+        assert!(matches("a*b*c", "abcbc"));
+        assert!(!matches("a*b*c", "acb"));
+        assert!(matches("*", ""));
+        assert!(matches("**", "anything"));
+    }
+
+    #[test]
+    fn question_mark() {
+        assert!(matches("web?", "web1"));
+        assert!(!matches("web?", "web"));
+        assert!(!matches("web?", "web12"));
+        assert!(matches("?*", "日"));
+        assert!(!matches("?*", ""));
+    }
+
+    #[test]
+    fn empty() {
+        assert!(matches("", ""));
+        assert!(!matches("", "prod"));
+    }
+
+    #[test]
+    fn any() {
+        assert!(matches_any("prod*, staging", "staging"));
+        assert!(matches_any("prod*, staging", "prod-eu"));
+        assert!(!matches_any("prod*, staging", "dev"));
+    }
+}
@@ -0,0 +1,43 @@
+use powerline_rs::background::Background;
+use std::env;
+
+const BASH: &str = include_str!("init/bash.sh");
+const ZSH:  &str = include_str!("init/zsh.sh");
+const FISH: &str = include_str!("init/fish.sh");
+const ION:  &str = include_str!("init/ion.sh");
+
+/// Quotes a string so bash, zsh, fish and ion all read it back literally
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Returns the integration script for a shell, calling this executable with
+/// any extra arguments. A known terminal background is exported so prompts
+/// don't have to ask the terminal themselves.
+pub fn script(shell: &str, args: &[&str], background: Option<Background>) -> String {
+    let template = match shell {
+        "bash" => BASH,
+        "zsh"  => ZSH,
+        "fish" => FISH,
+        "ion"  => ION,
+        _ => unreachable!()
+    };
+    let exe = env::current_exe()
+        .ok()
+        .and_then(|path| path.to_str().map(quote))
+        .unwrap_or_else(|| String::from("powerline-rs"));
+    let args: Vec<_> = args.iter().map(|arg| quote(arg)).collect();
+    let background = background.map(|background| {
+        let export = match shell {
+            "fish" => format!("set -gx POWERLINE_RS_BACKGROUND {}", background.name()),
+            "ion"  => format!("export POWERLINE_RS_BACKGROUND = {}", background.name()),
+            _      => format!("export POWERLINE_RS_BACKGROUND={}", background.name())
+        };
+        format!("# The terminal background, for --theme-light and --theme-dark\n{}\n\n", export)
+    });
+
+    template
+        .replace("__BACKGROUND__\n", background.as_ref().map_or("", String::as_str))
+        .replace("__POWERLINE__", &exe)
+        .replace(" __ARGS__", &if args.is_empty() { String::new() } else { format!(" {}", args.join(" ")) })
+}
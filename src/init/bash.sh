@@ -0,0 +1,66 @@
+# powerline-rs integration for bash, load with:
+#   eval "$(powerline-rs init bash)"
+#
+# The job count comes from bash itself, through \j in the prompt.
+
+# Count nested ssh sessions for the ssh module, once per connection
+if [ -n "$SSH_CONNECTION" ] && [ "$LC_POWERLINE_SSH" != "$SSH_CONNECTION" ]; then
+    export LC_POWERLINE_SSH_DEPTH=$((${LC_POWERLINE_SSH_DEPTH:-0} + 1)) LC_POWERLINE_SSH="$SSH_CONNECTION"
+fi
+
+__BACKGROUND__
+# The DEBUG trap also fires for everything in PROMPT_COMMAND, so the start
+# time is only taken for the first command after the prompt is armed at the
+# end of PROMPT_COMMAND. Otherwise time spent at the prompt would count.
+__powerline_preexec() {
+    local exit_code=$?
+    if [ -n "$__powerline_armed" ]; then
+        __powerline_start="$EPOCHREALTIME"
+        unset __powerline_armed
+    fi
+    # Whatever trap runs after this may want the real status
+    return $exit_code
+}
+
+# The command and arguments the prompt is drawn with, which hooks like
+# the transient prompt in contrib reuse
+__powerline_command=(__POWERLINE__ __ARGS__)
+
+__powerline_prompt() {
+    # Both have to be read in the same statement, before anything else runs
+    local exit_code=$? statuses="${PIPESTATUS[*]}"
+    local args=(--shell bash --pipestatus "${statuses// /,}")
+    [ -n "$__powerline_start" ] && args+=(--duration-start "$__powerline_start")
+    PS1="$("${__powerline_command[@]}" "${args[@]}" "$exit_code")"
+    unset __powerline_start
+}
+
+# Keep any DEBUG trap that's already set, like bash-preexec's, running
+# after ours
+__powerline_debug_trap="$(trap -p DEBUG)"
+__powerline_debug_trap="${__powerline_debug_trap#trap -- }"
+eval "__powerline_debug_trap=${__powerline_debug_trap% DEBUG}"
+case "$__powerline_debug_trap" in
+    *__powerline_preexec*) ;;
+    "") trap __powerline_preexec DEBUG ;;
+    *) trap "__powerline_preexec; $__powerline_debug_trap" DEBUG ;;
+esac
+unset __powerline_debug_trap
+
+# Run first, so things like `history -a` don't overwrite the exit status,
+# and arm the start time last. Only once though, or sourcing this again
+# would have the second run see the status of the first.
+if [[ "${PROMPT_COMMAND[*]}" != *__powerline_prompt* ]]; then
+    if (( BASH_VERSINFO[0] * 100 + BASH_VERSINFO[1] >= 501 )); then
+        PROMPT_COMMAND=(__powerline_prompt "${PROMPT_COMMAND[@]}" __powerline_armed=1)
+    else
+        # Older versions only run a string, where a command that already
+        # ends in `;` can't be followed by another `;`
+        __powerline_prompt_command="$PROMPT_COMMAND"
+        while [[ "$__powerline_prompt_command" == *[\;[:space:]] ]]; do
+            __powerline_prompt_command="${__powerline_prompt_command%?}"
+        done
+        PROMPT_COMMAND="__powerline_prompt${__powerline_prompt_command:+; $__powerline_prompt_command}; __powerline_armed=1"
+        unset __powerline_prompt_command
+    fi
+fi
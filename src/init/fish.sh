@@ -0,0 +1,18 @@
+# powerline-rs integration for fish, load with:
+#   powerline-rs init fish | source
+
+# Count nested ssh sessions for the ssh module, once per connection
+if set -q SSH_CONNECTION; and test "$LC_POWERLINE_SSH" != "$SSH_CONNECTION"
+    set -q LC_POWERLINE_SSH_DEPTH; or set -gx LC_POWERLINE_SSH_DEPTH 0
+    set -gx LC_POWERLINE_SSH_DEPTH (math $LC_POWERLINE_SSH_DEPTH + 1)
+    set -gx LC_POWERLINE_SSH "$SSH_CONNECTION"
+end
+
+__BACKGROUND__
+function fish_prompt
+    # Both have to be read before anything else runs
+    set -l statuses $pipestatus
+    set -l exit_code $status
+    __POWERLINE__ --shell bare --pipestatus (string join , $statuses) \
+        --jobs (count (jobs -p)) --duration "$CMD_DURATION" __ARGS__ $exit_code
+end
@@ -0,0 +1,22 @@
+# powerline-rs integration for ion, load by saving this to a file and
+# sourcing it from your initrc.
+#
+# Ion has no pipeline status and no hook that runs before a command, so
+# only the exit status and job count are passed. The pipestatus and
+# duration of the last command aren't available.
+
+# Count nested ssh sessions for the ssh module, once per connection
+if exists -s SSH_CONNECTION && test "$LC_POWERLINE_SSH" != "$SSH_CONNECTION"
+    if not exists -s LC_POWERLINE_SSH_DEPTH
+        export LC_POWERLINE_SSH_DEPTH = 0
+    end
+    export LC_POWERLINE_SSH_DEPTH = $(( $LC_POWERLINE_SSH_DEPTH + 1 ))
+    export LC_POWERLINE_SSH = $SSH_CONNECTION
+end
+
+__BACKGROUND__
+fn PROMPT
+    # Has to be read before anything else runs
+    let exit_code = ${?}
+    __POWERLINE__ --shell bare --jobs $(jobs | wc -l) __ARGS__ $exit_code
+end
@@ -0,0 +1,35 @@
+# powerline-rs integration for zsh, load with:
+#   eval "$(powerline-rs init zsh)"
+
+zmodload zsh/datetime zsh/parameter
+
+# Count nested ssh sessions for the ssh module, once per connection
+if [ -n "$SSH_CONNECTION" ] && [ "$LC_POWERLINE_SSH" != "$SSH_CONNECTION" ]; then
+    export LC_POWERLINE_SSH_DEPTH=$((${LC_POWERLINE_SSH_DEPTH:-0} + 1)) LC_POWERLINE_SSH="$SSH_CONNECTION"
+fi
+
+__BACKGROUND__
+__powerline_preexec() {
+    __powerline_start="$EPOCHREALTIME"
+}
+
+# The command and arguments the prompt is drawn with, which hooks like
+# the transient prompt in contrib reuse
+__powerline_command=(__POWERLINE__ __ARGS__)
+
+__powerline_precmd() {
+    # Both have to be read in the same statement, before anything else runs
+    local exit_code=$? statuses="${(j:,:)pipestatus}"
+    local args=(--shell zsh --pipestatus "$statuses" --jobs "${#jobstates}")
+    [ -n "$__powerline_start" ] && args+=(--duration-start "$__powerline_start")
+    PS1="$("${__powerline_command[@]}" "${args[@]}" "$exit_code")"
+    unset __powerline_start
+}
+
+# Run first, so other hooks don't overwrite the exit status. Only once
+# though, or sourcing this again would have the second run see the status
+# of the first.
+if (( ! ${precmd_functions[(I)__powerline_precmd]} )); then
+    preexec_functions+=(__powerline_preexec)
+    precmd_functions=(__powerline_precmd $precmd_functions)
+fi
@@ -0,0 +1,290 @@
+pub mod background;
+mod command;
+pub mod format;
+mod glob;
+pub mod module;
+pub mod render;
+pub mod segments;
+pub mod theme;
+pub mod title;
+
+#[cfg(test)] mod test_util;
+
+pub use crate::module::Module;
+pub use crate::segments::Segment;
+pub use crate::theme::Theme;
+
+use crate::module::{GIT_LINK_TEMPLATE_DEFAULT, TIME_FORMAT_DEFAULT};
+use std::{path::PathBuf, time::Duration};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Shell {
+    Bare,
+    Bash,
+    Zsh
+}
+
+pub struct Powerline {
+    segments: Vec<Segment>,
+    theme: Theme,
+    shell: Shell,
+
+    #[cfg(feature = "git2")]
+    git: Option<git2::Repository>,
+    #[cfg(feature = "git2")]
+    git_statuses: Option<Vec<git2::Status>>
+}
+impl Powerline {
+    pub fn new(theme: Theme, shell: Shell) -> Self {
+        Powerline {
+            segments: Vec::with_capacity(16), // just a guess
+            theme,
+            shell,
+
+            #[cfg(feature = "git2")]
+            git: None,
+            #[cfg(feature = "git2")]
+            git_statuses: None
+        }
+    }
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+    pub fn shell(&self) -> Shell {
+        self.shell
+    }
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+    /// Adds a segment of your own after the ones already there
+    pub fn push(&mut self, segment: Segment) {
+        self.segments.push(segment);
+    }
+    /// Renders the prompt into `out`, returning its visible width
+    pub fn render<W: std::fmt::Write>(&mut self, out: &mut W, rtl: bool) -> Result<usize, std::fmt::Error> {
+        render::render(out, &mut self.segments, self.shell, &self.theme, rtl)
+    }
+    pub fn print(&mut self, rtl: bool) {
+        let stdout = std::io::stdout();
+        render::render_io(&mut stdout.lock(), &mut self.segments, self.shell, &self.theme, rtl)
+            .expect("failed to write to stdout");
+    }
+}
+
+/// Builds a prompt out of modules, with the same options as the command line.
+///
+/// ```no_run
+/// use powerline_rs::{theme, Builder, Module, Shell};
+///
+/// let mut p = Builder::new(theme::DEFAULT, Shell::Bare)
+///     .modules(vec![Module::Cwd, Module::Git, Module::Root])
+///     .error(1)
+///     .build();
+/// let mut prompt = String::new();
+/// p.render(&mut prompt, false).unwrap();
+/// ```
+pub struct Builder {
+    theme: Theme,
+    shell: Shell,
+    modules: Vec<Module>,
+
+    cwd_max_depth: u8,
+    error: u8,
+    pipestatus: Option<Vec<u8>>,
+    error_verbose: bool,
+    duration: Option<Duration>,
+    duration_min: Duration,
+    jobs: Option<u32>,
+    time_format: String,
+    hyperlinks: bool,
+    git_link_template: String,
+    toolchain_active: bool,
+    ssh_client_ip: bool,
+    user_host: segments::UserHostOptions,
+    battery_threshold: u8,
+    load_threshold: u8,
+    mem_threshold: u8,
+    disk_threshold: u8,
+    sysfs_root: PathBuf,
+    proc_root: PathBuf,
+    theme_path: Option<String>
+}
+impl Builder {
+    pub fn new(theme: Theme, shell: Shell) -> Self {
+        Builder {
+            theme,
+            shell,
+            modules: Vec::new(),
+
+            cwd_max_depth: 5,
+            error: 0,
+            pipestatus: None,
+            error_verbose: false,
+            duration: None,
+            duration_min: Duration::from_millis(2000),
+            jobs: None,
+            time_format: String::from(TIME_FORMAT_DEFAULT),
+            hyperlinks: false,
+            git_link_template: String::from(GIT_LINK_TEMPLATE_DEFAULT),
+            toolchain_active: false,
+            ssh_client_ip: false,
+            user_host: segments::UserHostOptions::default(),
+            battery_threshold: 100,
+            load_threshold: 0,
+            mem_threshold: 0,
+            disk_threshold: 0,
+            sysfs_root: PathBuf::from("/sys"),
+            proc_root: PathBuf::from("/proc"),
+            theme_path: None
+        }
+    }
+    pub fn modules(mut self, modules: Vec<Module>) -> Self {
+        self.modules = modules;
+        self
+    }
+    pub fn cwd_max_depth(mut self, depth: u8) -> Self {
+        self.cwd_max_depth = depth;
+        self
+    }
+    /// The exit code of the last command
+    pub fn error(mut self, error: u8) -> Self {
+        self.error = error;
+        self
+    }
+    /// The exit codes of every command in the last pipeline, which default
+    /// to just the one from `error`
+    pub fn pipestatus(mut self, pipestatus: Vec<u8>) -> Self {
+        self.pipestatus = Some(pipestatus);
+        self
+    }
+    pub fn error_verbose(mut self, verbose: bool) -> Self {
+        self.error_verbose = verbose;
+        self
+    }
+    pub fn duration(mut self, duration: Option<Duration>) -> Self {
+        self.duration = duration;
+        self
+    }
+    pub fn duration_min(mut self, min: Duration) -> Self {
+        self.duration_min = min;
+        self
+    }
+    pub fn jobs(mut self, jobs: Option<u32>) -> Self {
+        self.jobs = jobs;
+        self
+    }
+    pub fn time_format<S: Into<String>>(mut self, format: S) -> Self {
+        self.time_format = format.into();
+        self
+    }
+    pub fn hyperlinks(mut self, hyperlinks: bool) -> Self {
+        self.hyperlinks = hyperlinks;
+        self
+    }
+    pub fn git_link_template<S: Into<String>>(mut self, template: S) -> Self {
+        self.git_link_template = template.into();
+        self
+    }
+    /// Makes the toolchain modules also show the version in $PATH, which
+    /// means running it (with a cache)
+    pub fn toolchain_active(mut self, active: bool) -> Self {
+        self.toolchain_active = active;
+        self
+    }
+    /// Makes the ssh module show the address connected from
+    pub fn ssh_client_ip(mut self, show: bool) -> Self {
+        self.ssh_client_ip = show;
+        self
+    }
+    /// When to show the user and host modules
+    pub fn user_host(mut self, options: segments::UserHostOptions) -> Self {
+        self.user_host = options;
+        self
+    }
+    /// Hides the battery module above this percentage
+    pub fn battery_threshold(mut self, threshold: u8) -> Self {
+        self.battery_threshold = threshold;
+        self
+    }
+    /// Hides the load module below this percentage of the CPU count
+    pub fn load_threshold(mut self, threshold: u8) -> Self {
+        self.load_threshold = threshold;
+        self
+    }
+    /// Hides the mem module below this percentage used
+    pub fn mem_threshold(mut self, threshold: u8) -> Self {
+        self.mem_threshold = threshold;
+        self
+    }
+    /// Hides the disk module below this percentage used
+    pub fn disk_threshold(mut self, threshold: u8) -> Self {
+        self.disk_threshold = threshold;
+        self
+    }
+    /// Where sysfs is mounted, which can be changed for tests
+    pub fn sysfs_root<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.sysfs_root = root.into();
+        self
+    }
+    /// Where procfs is mounted, which can be changed for tests
+    pub fn proc_root<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.proc_root = root.into();
+        self
+    }
+    /// The theme file plugins get told about
+    pub fn theme_path<S: Into<String>>(mut self, path: Option<S>) -> Self {
+        self.theme_path = path.map(Into::into);
+        self
+    }
+    /// Runs all modules in order
+    pub fn build(self) -> Powerline {
+        let (error, hyperlinks) = (self.error, self.hyperlinks);
+        let pipestatus = self.pipestatus.unwrap_or_else(|| vec![error]);
+        let mut p = Powerline::new(self.theme, self.shell);
+        // Shared by the user and host modules, and not free to work out
+        let (user_host, proc_root, mut user_host_visible) = (&self.user_host, &self.proc_root, None);
+
+        for &module in &self.modules {
+            match module {
+                Module::Battery => segments::segment_battery(&mut p, &self.sysfs_root, self.battery_threshold),
+                Module::Cloud => segments::segment_cloud(&mut p),
+                Module::Container => segments::segment_container(&mut p, &self.proc_root),
+                Module::Custom => segments::segment_custom(&mut p),
+                Module::Cwd => segments::segment_cwd(&mut p, self.cwd_max_depth, hyperlinks),
+                Module::Disk => segments::segment_disk(&mut p, self.disk_threshold),
+                Module::Duration => segments::segment_duration(&mut p, self.duration, self.duration_min),
+                Module::Env => segments::segment_env(&mut p),
+                Module::Git => { #[cfg(feature = "git2")] segments::segment_git(&mut p, Some(&*self.git_link_template).filter(|_| hyperlinks)) },
+                Module::GitStage => { #[cfg(feature = "git2")] segments::segment_gitstage(&mut p) },
+                Module::Go => segments::segment_go(&mut p, self.toolchain_active),
+                Module::Host => {
+                    let visible = *user_host_visible.get_or_insert_with(|| user_host.visible(proc_root));
+                    segments::segment_host(&mut p, user_host, visible)
+                },
+                Module::Jobs => segments::segment_jobs(&mut p, self.jobs),
+                Module::Kube => segments::segment_kube(&mut p),
+                Module::Load => segments::segment_load(&mut p, &self.proc_root, self.load_threshold),
+                Module::Mem => segments::segment_mem(&mut p, &self.proc_root, self.mem_threshold),
+                Module::NixShell => segments::segment_nix(&mut p),
+                Module::Node => segments::segment_node(&mut p, self.toolchain_active),
+                Module::Perms => segments::segment_perms(&mut p),
+                Module::Plugin => { #[cfg(feature = "serde_json")] segments::segment_plugin(&mut p, error, self.theme_path.as_deref()) },
+                Module::Prompt => segments::segment_prompt(&mut p, &pipestatus),
+                Module::Python => segments::segment_python(&mut p, self.toolchain_active),
+                Module::Ps => segments::segment_ps(&mut p, &self.proc_root),
+                Module::Root => segments::segment_root(&mut p, &pipestatus, self.error_verbose),
+                Module::Rust => segments::segment_rust(&mut p, self.toolchain_active),
+                Module::Ssh => segments::segment_ssh(&mut p, &self.proc_root, self.ssh_client_ip),
+                Module::Time => segments::segment_time(&mut p, &self.time_format),
+                Module::User => {
+                    let visible = *user_host_visible.get_or_insert_with(|| user_host.visible(proc_root));
+                    segments::segment_user(&mut p, user_host, visible)
+                },
+                Module::LineBreak => segments::segment_linebreak(&mut p),
+                Module::VirtualEnv => segments::segment_virtualenv(&mut p),
+            }
+        }
+
+        p
+    }
+}
@@ -2,31 +2,36 @@
 extern crate clap;
 
 mod cli;
-mod format;
-mod module;
-mod segments;
-mod theme;
-
-use crate::module::Module;
-use crate::segments::Segment;
-use crate::theme::Theme;
-
-#[derive(Clone, Copy, Eq, PartialEq)]
-pub enum Shell {
-    Bare,
-    Bash,
-    Zsh
+mod init;
+mod preview;
+
+use powerline_rs::{background::{self, Background}, format, module, segments::UserHostOptions, theme, title, Builder, Shell, Theme};
+use std::{
+    io::{self, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH}
+};
+
+fn load_theme_or_exit(file: &str) -> Theme {
+    match theme::load(file) {
+        Ok(theme) => theme,
+        Err(err) => {
+            eprintln!("Invalid theme: {}", err);
+            std::process::exit(1);
+        }
+    }
 }
 
-pub struct Powerline {
-    segments: Vec<Segment>,
-    theme: Theme,
-    shell: Shell,
-
-    #[cfg(feature = "git2")]
-    git: Option<git2::Repository>,
-    #[cfg(feature = "git2")]
-    git_statuses: Option<Vec<git2::Status>>
+/// Picks the theme file to use, choosing between --theme-light and
+/// --theme-dark based on the terminal background if either is set.
+fn theme_file<'a>(matches: &'a clap::ArgMatches<'a>) -> Option<&'a str> {
+    let (light, dark) = (matches.value_of("theme-light"), matches.value_of("theme-dark"));
+    if light.is_none() && dark.is_none() {
+        return matches.value_of("theme");
+    }
+    match background::detect() {
+        Some(Background::Light) => light,
+        Some(Background::Dark) | None => dark
+    }.or_else(|| matches.value_of("theme"))
 }
 
 fn main() {
@@ -38,17 +43,75 @@ fn main() {
     #[cfg(feature = "flame")]
     flame::end("clap-rs");
 
+    if let ("init", Some(sub)) = matches.subcommand() {
+        let args: Vec<_> = sub.values_of("args").map(|args| args.collect()).unwrap_or_default();
+        // Asking the terminal is slow when it doesn't answer, so only do it
+        // once here, and only if a theme depends on it
+        let themed = args.iter().any(|arg| arg.starts_with("--theme-light") || arg.starts_with("--theme-dark"));
+        let background = if themed && background::detect().is_none() { background::query() } else { None };
+        print!("{}", init::script(sub.value_of("shell").unwrap(), &args, background));
+        return;
+    }
+    if let ("theme", Some(matches_theme)) = matches.subcommand() {
+        match matches_theme.subcommand() {
+            ("preview", Some(sub)) => preview::preview(load_theme_or_exit(sub.value_of("file").unwrap())),
+            ("dump", Some(sub)) => {
+                let theme = match sub.value_of("file").or_else(|| theme_file(&matches)) {
+                    Some(file) => load_theme_or_exit(file),
+                    None => theme::DEFAULT
+                };
+                let stdout = std::io::stdout();
+                theme::dump(&theme, &mut stdout.lock()).expect("failed to write to stdout");
+            },
+            _ => unreachable!()
+        }
+        return;
+    }
+
     #[cfg(feature = "flame")]
     flame::start("parse arguments");
 
     let cwd_max_depth    = value_t_or_exit!(matches, "cwd-max-depth", u8);
     // let cwd_max_dir_size = value_t_or_exit!(matches, "cwd-max-dir-size", u8);
     let error            = value_t_or_exit!(matches, "error", u8);
+    let error_verbose    = matches.is_present("error-verbose");
+    // Hooks may pass an empty list, so skip anything unparsable rather than
+    // failing to print a prompt
+    let pipestatus: Vec<u8> = matches.values_of("pipestatus")
+        .map(|statuses| statuses.filter_map(|status| status.trim().parse().ok()).collect())
+        .unwrap_or_default();
+    let pipestatus = if pipestatus.is_empty() { vec![error] } else { pipestatus };
+    let battery_threshold = value_t_or_exit!(matches, "battery-threshold", u8);
+    let load_threshold   = value_t_or_exit!(matches, "load-threshold", u8);
+    let mem_threshold    = value_t_or_exit!(matches, "mem-threshold", u8);
+    let disk_threshold   = value_t_or_exit!(matches, "disk-threshold", u8);
+    let duration_min     = Duration::from_millis(value_t_or_exit!(matches, "duration-min", u64));
+
+    // Shell hooks may pass empty values, or use ',' as the decimal separator
+    // depending on the locale. A missing duration is better than no prompt.
+    let timestamp = |name| matches.value_of(name).and_then(|value: &str| value.replace(',', ".").parse::<f64>().ok());
+    let duration = if let Some(ms) = matches.value_of("duration").and_then(|ms| ms.parse().ok()) {
+        Some(Duration::from_millis(ms))
+    } else if let Some(start) = timestamp("duration-start") {
+        let end = timestamp("duration-end").unwrap_or_else(|| {
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|now| now.as_secs_f64()).unwrap_or(start)
+        });
+        Some(Duration::from_secs_f64((end - start).max(0.0)))
+    } else { None };
+    let only: Vec<_> = matches.values_of("user-host-only").map(|only| only.collect()).unwrap_or_default();
+    let user_host = UserHostOptions {
+        default_user: matches.value_of("default-user").map(String::from),
+        only_ssh: only.contains(&"ssh"),
+        only_switched: only.contains(&"switched"),
+        combined: matches.is_present("user-host-combined")
+    };
+    let jobs = matches.value_of("jobs").and_then(|jobs| jobs.trim().parse().ok());
 
     #[cfg(feature = "flame")]
     flame::start("parse theme");
 
-    let theme = if let Some(file) = matches.value_of("theme") {
+    let theme_path = theme_file(&matches);
+    let theme = if let Some(file) = theme_path {
         if let Ok(theme) = theme::load(file) {
             theme
         } else {
@@ -64,13 +127,11 @@ fn main() {
     flame::start("parse modules");
 
     let modules: Vec<_> = matches
-        .values_of("modules")
+        .values_of(if matches.is_present("transient") { "transient-modules" } else { "modules" })
         .unwrap()
         .map(|module| module.parse().unwrap())
         .collect();
 
-    let time_format = matches.value_of("time_format").unwrap();
-
     #[cfg(feature = "flame")]
     flame::end("parse modules");
 
@@ -80,66 +141,71 @@ fn main() {
     #[cfg(feature = "flame")]
     flame::start("main");
 
-    let mut p = Powerline {
-        segments: Vec::with_capacity(16), // just a guess
-        theme,
-        shell: match matches.value_of("shell").unwrap() {
-            "bare" => Shell::Bare,
-            "bash" => Shell::Bash,
-            "zsh"  => Shell::Zsh,
-            _ => unreachable!()
-        },
-
-        #[cfg(feature = "git2")]
-        git: None,
-        #[cfg(feature = "git2")]
-        git_statuses: None
+    let shell = match matches.value_of("shell").unwrap() {
+        "bare" => Shell::Bare,
+        "bash" => Shell::Bash,
+        "zsh"  => Shell::Zsh,
+        _ => unreachable!()
     };
-
-    for module in modules {
-        match module {
-            Module::Cwd => segments::segment_cwd(&mut p, cwd_max_depth),
-            Module::Git => { #[cfg(feature = "git2")] segments::segment_git(&mut p) },
-            Module::GitStage => { #[cfg(feature = "git2")] segments::segment_gitstage(&mut p) },
-            Module::Host => segments::segment_host(&mut p),
-            Module::Jobs => segments::segment_jobs(&mut p),
-            Module::NixShell => segments::segment_nix(&mut p),
-            Module::Perms => segments::segment_perms(&mut p),
-            Module::Ps => segments::segment_ps(&mut p),
-            Module::Root => segments::segment_root(&mut p, error),
-            Module::Ssh => segments::segment_ssh(&mut p),
-            Module::Time => segments::segment_time(&mut p, &time_format),
-            Module::User => segments::segment_user(&mut p),
-            Module::LineBreak => segments::segment_linebreak(&mut p),
-            Module::VirtualEnv => segments::segment_virtualenv(&mut p),
-        }
-    }
+    let mut p = Builder::new(theme, shell)
+        .modules(modules)
+        .cwd_max_depth(cwd_max_depth)
+        .error(error)
+        .pipestatus(pipestatus)
+        .error_verbose(error_verbose)
+        .duration(duration)
+        .duration_min(duration_min)
+        .jobs(jobs)
+        .time_format(matches.value_of("time_format").unwrap())
+        .hyperlinks(matches.is_present("hyperlinks"))
+        .git_link_template(matches.value_of("git-link-template").unwrap())
+        .toolchain_active(matches.is_present("toolchain-active"))
+        .ssh_client_ip(matches.is_present("ssh-client-ip"))
+        .user_host(user_host)
+        .battery_threshold(battery_threshold)
+        .load_threshold(load_threshold)
+        .mem_threshold(mem_threshold)
+        .disk_threshold(disk_threshold)
+        .theme_path(theme_path)
+        .build();
 
     #[cfg(feature = "flame")]
     flame::end("main");
     #[cfg(feature = "flame")]
     flame::start("print");
 
-    if matches.is_present("rtl") {
-        let n = p.segments.len();
-        for i in 1..n+1 {
-            p.segments[n-i].escape(p.shell);
-            p.segments[n-i].print_rtl(p.segments.get(n-i+1), p.shell, &p.theme);
-        }
-    } else {
-        print!("{}\u{e0b6}", format::fg(p.shell, p.segments[0].bg));
-        for i in 0..p.segments.len() {
-            p.segments[i].escape(p.shell);
-            p.segments[i].print(p.segments.get(i+1), p.shell, &p.theme);
+    // Built up in full and written at once, so the shell never reads half a
+    // prompt
+    let mut out = String::new();
+
+    // Right prompts aren't where commands are typed, so they don't get marks
+    let semantic = matches.is_present("semantic-prompt") && !matches.is_present("rtl");
+    if let Some(template) = matches.value_of("title").filter(|_| !matches.is_present("rtl")) {
+        if let Some(title) = title::title(&mut p, template) {
+            out.push_str(&title);
         }
     }
+    if semantic {
+        out.push_str(&format::osc(shell, &format!("133;D;{}", error)));
+        out.push_str(&format::osc(shell, "133;A"));
+    }
+
+    p.render(&mut out, matches.is_present("rtl")).expect("failed to render the prompt");
 
-    if matches.is_present("newline") {
-        println!();
+    if matches.is_present("newline") && !matches.is_present("transient") {
+        out.push('\n');
     } else if !matches.is_present("rtl") {
-        print!(" ");
+        out.push(' ');
     }
 
+    if semantic {
+        out.push_str(&format::osc(shell, "133;B"));
+    }
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    stdout.write_all(out.as_bytes()).and_then(|()| stdout.flush()).expect("failed to write to stdout");
+
     #[cfg(feature = "flame")]
     flame::end("print");
 
@@ -1,15 +1,36 @@
 use std::str::FromStr;
 
+/// The time module's default format, which bash and zsh can render themselves
+pub const TIME_FORMAT_DEFAULT: &str = "%I:%M %p";
+/// Where the git module links branches to, GitHub style
+pub const GIT_LINK_TEMPLATE_DEFAULT: &str = "https://{host}/{path}/tree/{branch}";
+
 pub const ALL: &[&str] = &[
+    "battery",
+    "cloud",
+    "container",
+    "custom",
     "cwd",
+    "disk",
+    "duration",
+    "env",
     "git",
     "gitstage",
+    "go",
     "host",
     "jobs",
+    "kube",
+    "load",
+    "mem",
     "nix-shell",
+    "node",
     "perms",
+    "plugin",
+    "prompt",
+    "python",
     "ps",
     "root",
+    "rust",
     "ssh",
     "time",
     "user",
@@ -17,17 +38,33 @@ pub const ALL: &[&str] = &[
     "linebreak",
 ];
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Module {
+    Battery,
+    Cloud,
+    Container,
+    Custom,
     Cwd,
+    Disk,
+    Duration,
+    Env,
     Git,
     GitStage,
+    Go,
     Host,
     Jobs,
+    Kube,
+    Load,
+    Mem,
     NixShell,
+    Node,
     Perms,
+    Plugin,
+    Prompt,
+    Python,
     Ps,
     Root,
+    Rust,
     Ssh,
     Time,
     User,
@@ -39,15 +76,31 @@ impl FromStr for Module {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
+            "battery"    => Ok(Module::Battery),
+            "cloud"      => Ok(Module::Cloud),
+            "container"  => Ok(Module::Container),
+            "custom"     => Ok(Module::Custom),
             "cwd"        => Ok(Module::Cwd),
+            "disk"       => Ok(Module::Disk),
+            "duration"   => Ok(Module::Duration),
+            "env"        => Ok(Module::Env),
             "git"        => Ok(Module::Git),
             "gitstage"   => Ok(Module::GitStage),
+            "go"         => Ok(Module::Go),
             "host"       => Ok(Module::Host),
             "jobs"       => Ok(Module::Jobs),
+            "kube"       => Ok(Module::Kube),
+            "load"       => Ok(Module::Load),
+            "mem"        => Ok(Module::Mem),
             "nix-shell"  => Ok(Module::NixShell),
+            "node"       => Ok(Module::Node),
             "perms"      => Ok(Module::Perms),
+            "plugin"     => Ok(Module::Plugin),
+            "prompt"     => Ok(Module::Prompt),
+            "python"     => Ok(Module::Python),
             "ps"         => Ok(Module::Ps),
             "root"       => Ok(Module::Root),
+            "rust"       => Ok(Module::Rust),
             "ssh"        => Ok(Module::Ssh),
             "time"       => Ok(Module::Time),
             "user"       => Ok(Module::User),
@@ -0,0 +1,132 @@
+use powerline_rs::{format, segments, Powerline, Segment, Shell, Theme};
+use std::{env, fs, path::{Path, PathBuf}, process, thread, time::Duration};
+
+fn print_line(theme: &Theme, build: impl FnOnce(&mut Powerline)) {
+    let mut p = Powerline::new(theme.clone(), Shell::Bare);
+    build(&mut p);
+    p.print(false);
+    println!();
+}
+
+/// Writes fake sysfs and /proc files, so the battery, load and mem segments
+/// can be shown at each of the theme's levels
+fn fixture(theme: &Theme) -> PathBuf {
+    let root = env::temp_dir().join(format!("powerline-rs-preview-{}", process::id()));
+    let write = |path: &Path, content: String| {
+        fs::create_dir_all(path.parent().unwrap()).ok();
+        fs::write(path, content).ok();
+    };
+    let batteries = [
+        ("charging", 80, "Charging"),
+        ("warning", theme.battery_warning_level, "Discharging"),
+        ("critical", theme.battery_critical_level, "Discharging")
+    ];
+    for &(name, capacity, status) in &batteries {
+        let dir = root.join(name).join("class").join("power_supply").join("BAT0");
+        write(&dir.join("type"), String::from("Battery\n"));
+        write(&dir.join("capacity"), format!("{}\n", capacity));
+        write(&dir.join("status"), format!("{}\n", status));
+    }
+
+    // Load is relative to the CPU count
+    let cpus = thread::available_parallelism().map(|cpus| cpus.get()).unwrap_or(1) as f64;
+    write(&root.join("idle").join("loadavg"), format!("{:.2} 0.00 0.00 1/100 1\n", cpus * 0.1));
+    write(&root.join("busy").join("loadavg"), format!("{:.2} 0.00 0.00 1/100 1\n", cpus * f64::from(theme.load_warning_level) / 100.0));
+    write(&root.join("idle").join("meminfo"), String::from("MemTotal: 1000 kB\nMemAvailable: 590 kB\n"));
+    let available = 1000 - u32::from(theme.mem_critical_level) * 10;
+    write(&root.join("busy").join("meminfo"), format!("MemTotal: 1000 kB\nMemAvailable: {} kB\n", available));
+    root
+}
+
+pub fn preview(theme: Theme) {
+    // Normal user, passing command
+    print_line(&theme, |p| {
+        let t = p.theme().clone();
+        let cwd = String::from("~/projects/") + &format::as_bold(Shell::Bare, "powerline-rs");
+        p.push(Segment::new(t.username_bg, t.username_fg, "user").bold());
+        p.push(Segment::new(t.hostname_bg, t.hostname_fg, "hostname"));
+        p.push(Segment::new(t.ssh_bg, t.ssh_fg, t.ssh_char.to_string()));
+        p.push(Segment::new(t.path_bg, t.path_fg, cwd).dont_escape());
+        segments::segment_root(p, &[0], false);
+        segments::segment_prompt(p, &[0]);
+    });
+
+    // Root user, read-only directory, failing pipeline
+    print_line(&theme, |p| {
+        let t = p.theme().clone();
+        let cwd = String::from("/") + &format::as_bold(Shell::Bare, "etc");
+        p.push(Segment::new(t.username_root_bg, t.username_root_fg, "root").bold());
+        p.push(Segment::new(t.hostname_bg, t.hostname_fg, "hostname"));
+        p.push(Segment::new(t.path_bg, t.path_fg, cwd).dont_escape());
+        p.push(Segment::new(t.ro_bg, t.ro_fg, t.ro_char.to_string()));
+        segments::segment_root(p, &[0, 130], true);
+        segments::segment_prompt(p, &[0, 130]);
+    });
+
+    // Clean branch, ahead and behind upstream
+    print_line(&theme, |p| {
+        let t = p.theme().clone();
+        p.push(Segment::new(t.git_clean_bg, t.git_clean_fg, " master"));
+        p.push(Segment::new(t.git_ahead_bg, t.git_ahead_fg, format!("{} {}", t.git_ahead_char, 2)));
+        p.push(Segment::new(t.git_behind_bg, t.git_behind_fg, format!("{} {}", t.git_behind_char, 1)));
+    });
+
+    // Dirty branch with all gitstage counters
+    print_line(&theme, |p| {
+        let t = p.theme().clone();
+        p.push(Segment::new(t.git_dirty_bg, t.git_dirty_fg, " feature"));
+        p.push(Segment::new(t.git_staged_bg, t.git_staged_fg, format!("{} {}", t.git_staged_char, 1)));
+        p.push(Segment::new(t.git_changed_bg, t.git_changed_fg, format!("{} {}", t.git_changed_char, 2)));
+        p.push(Segment::new(t.git_untracked_bg, t.git_untracked_fg, format!("{} {}", t.git_untracked_char, 3)));
+        p.push(Segment::new(t.git_conflicted_bg, t.git_conflicted_fg, format!("{} {}", t.git_conflicted_char, 4)));
+        p.push(Segment::new(t.git_stashed_bg, t.git_stashed_fg, format!("{} {}", t.git_stashed_char, 5)));
+    });
+
+    // Detached HEAD
+    print_line(&theme, |p| {
+        let t = p.theme().clone();
+        p.push(Segment::new(t.git_detached_bg, t.git_detached_fg, " 1a2b3c4"));
+    });
+
+    // Everything else
+    let root = fixture(&theme);
+    print_line(&theme, |p| {
+        let t = p.theme().clone();
+        let venv = format::as_bold(Shell::Bare, "venv");
+        p.push(Segment::new(t.time_bg, t.time_fg, "12:34 PM"));
+        segments::segment_duration(p, Some(Duration::from_secs(83)), Duration::from_secs(0));
+        segments::segment_jobs(p, Some(2));
+        p.push(Segment::new(t.ps_bg, t.ps_fg, "3"));
+        p.push(Segment::new(t.virtual_env_bg, t.virtual_env_fg, venv).dont_escape());
+        p.push(Segment::new(t.nixshell_bg, t.nixshell_fg, "impure"));
+        p.push(Segment::new(t.container_bg, t.container_fg, "fedora-toolbox-39"));
+        p.push(Segment::new(t.kube_bg, t.kube_fg, "dev/default"));
+        p.push(Segment::new(t.aws_bg, t.aws_fg, "dev/eu-west-1 7h59m"));
+        p.push(Segment::new(t.aws_expired_bg, t.aws_expired_fg, "prod/eu-west-1"));
+        p.push(Segment::new(t.gcp_bg, t.gcp_fg, "default/my-project"));
+        p.push(Segment::new(t.azure_bg, t.azure_fg, "Pay-As-You-Go"));
+        p.push(Segment::new(t.rust_bg, t.rust_fg, "rust 1.70 (1.72.0)"));
+        p.push(Segment::new(t.node_bg, t.node_fg, "node 20.1.0"));
+        p.push(Segment::new(t.python_bg, t.python_fg, "python 3.11"));
+        p.push(Segment::new(t.go_bg, t.go_fg, "go 1.21"));
+        for battery in &["charging", "warning", "critical"] {
+            segments::segment_battery(p, &root.join(battery), 100);
+        }
+        for state in &["idle", "busy"] {
+            segments::segment_load(p, &root.join(state), 0);
+            segments::segment_mem(p, &root.join(state), 0);
+        }
+        p.push(Segment::new(t.disk_bg, t.disk_fg, "disk 120G"));
+        p.push(Segment::new(t.disk_warning_bg, t.disk_warning_fg, "disk 8.5G"));
+        for entry in &t.kube {
+            let name = entry.alias.as_deref().unwrap_or_else(|| entry.context());
+            p.push(Segment::new(entry.bg.unwrap_or(t.kube_bg), entry.fg.unwrap_or(t.kube_fg), format!("{}/default", name)));
+        }
+        // Only the variables set in this shell
+        segments::segment_env(p);
+        for entry in &t.custom {
+            p.push(Segment::new(entry.bg.unwrap_or(t.custom_bg), entry.fg.unwrap_or(t.custom_fg), entry.id.clone()));
+        }
+    });
+    fs::remove_dir_all(&root).ok();
+}
@@ -0,0 +1,166 @@
+use std::{fmt::{self, Write as _}, io};
+use unicode_width::UnicodeWidthChar;
+use crate::{segments::Segment, theme::Theme, Shell};
+
+/// Renders the segments into `out`, returning how many columns the output
+/// takes up on screen.
+pub fn render<W: fmt::Write>(out: &mut W, segments: &mut [Segment], shell: Shell, theme: &Theme, rtl: bool) -> Result<usize, fmt::Error> {
+    let mut buf = String::new();
+
+    if rtl {
+        let n = segments.len();
+        for i in 1..n+1 {
+            segments[n-i].escape(shell);
+            segments[n-i].render_rtl(&mut buf, segments.get(n-i+1), shell, theme)?;
+        }
+    } else if let Some(first) = segments.first() {
+        write!(buf, "{}\u{e0b6}", crate::format::fg(shell, first.bg))?;
+        for i in 0..segments.len() {
+            segments[i].escape(shell);
+            segments[i].render(&mut buf, segments.get(i+1), shell, theme)?;
+        }
+    }
+
+    out.write_str(&buf)?;
+    Ok(visible_width(shell, &buf))
+}
+
+/// Same as `render`, but for byte streams like stdout
+pub fn render_io<W: io::Write>(out: &mut W, segments: &mut [Segment], shell: Shell, theme: &Theme, rtl: bool) -> io::Result<usize> {
+    let mut buf = String::new();
+    let width = render(&mut buf, segments, shell, theme, rtl)
+        .map_err(io::Error::other)?;
+    out.write_all(buf.as_bytes())?;
+    Ok(width)
+}
+
+/// Counts the columns a rendered prompt takes up, skipping everything the
+/// shell or terminal doesn't display. Shell escapes such as \u or %n are
+/// counted as a single column, since only the shell knows what they expand
+/// to.
+pub fn visible_width(shell: Shell, rendered: &str) -> usize {
+    let mut width = 0;
+    let mut chars = rendered.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (shell, c) {
+            (_, '\x1b') => match chars.next() {
+                // CSI, ends with a letter-ish final byte
+                Some('[') => for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                },
+                // OSC, ends with BEL or ESC \
+                Some(']') => while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                },
+                _ => ()
+            },
+            (Shell::Bash, '\\') => match chars.next() {
+                Some('[') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\\' && chars.next_if_eq(&']').is_some() {
+                            break;
+                        }
+                    }
+                },
+                Some(_) => width += 1,
+                None => ()
+            },
+            (Shell::Zsh, '%') => match chars.next() {
+                Some('{') => {
+                    while let Some(c) = chars.next() {
+                        if c == '%' && chars.next_if_eq(&'}').is_some() {
+                            break;
+                        }
+                    }
+                },
+                Some(_) => width += 1,
+                None => ()
+            },
+            (_, c) => width += c.width().unwrap_or(0)
+        }
+    }
+
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme;
+
+    fn segments() -> Vec<Segment> {
+        vec![
+            Segment::new(31, 15, "user").bold(),
+            Segment::new(236, 252, r#"a\b$c"d%e)f"#),
+            Segment::new(236, 2, "same bg"),
+            Segment::new(124, 231, "1")
+        ]
+    }
+
+    fn snapshot(shell: Shell, rtl: bool) -> (String, usize) {
+        let mut out = String::new();
+        let width = render(&mut out, &mut segments(), shell, &theme::DEFAULT, rtl).unwrap();
+        (out, width)
+    }
+
+    #[test]
+    fn bare_ltr() {
+        assert_eq!(snapshot(Shell::Bare, false), (
+            String::from("\u{1b}[38;5;31m\u{e0b6}\u{1b}[38;5;15m\u{1b}[48;5;31m \u{1b}[1muser\u{1b}[22m \u{1b}[38;5;31m\u{1b}[48;5;236m\u{e0c6}\u{1b}[38;5;252m\u{1b}[48;5;236m a\\b$c\"d%e)f\u{1b}[38;5;2m\u{1b}[48;5;236m same bg \u{1b}[38;5;236m\u{1b}[48;5;124m\u{e0c6}\u{1b}[38;5;231m\u{1b}[48;5;124m 1 \u{1b}[38;5;124m\u{1b}[49m\u{e0b0}\u{1b}[39m"),
+            34
+        ));
+    }
+
+    #[test]
+    fn bare_rtl() {
+        assert_eq!(snapshot(Shell::Bare, true), (
+            String::from("\u{1b}[38;5;124m\u{e0b2}\u{1b}[38;5;231m\u{1b}[48;5;124m 1 \u{1b}[49m\u{1b}[39m\u{1b}[38;5;236m\u{1b}[48;5;124m\u{e0b2}\u{1b}[38;5;2m\u{1b}[48;5;236m same bg \u{1b}[49m\u{1b}[39m\u{1b}[38;5;252m\u{1b}[48;5;236m a\\b$c\"d%e)f \u{1b}[49m\u{1b}[39m\u{1b}[38;5;31m\u{1b}[48;5;236m\u{e0b2}\u{1b}[38;5;15m\u{1b}[48;5;31m user \u{1b}[49m\u{1b}[39m"),
+            34
+        ));
+    }
+
+    #[test]
+    fn bash_ltr() {
+        assert_eq!(snapshot(Shell::Bash, false), (
+            String::from("\\[\\e[38;5;31m\\]\u{e0b6}\\[\\e[38;5;15m\\]\\[\\e[48;5;31m\\] \\[\\e[1m\\]user\\[\\e[22m\\] \\[\\e[38;5;31m\\]\\[\\e[48;5;236m\\]\u{e0c6}\\[\\e[38;5;252m\\]\\[\\e[48;5;236m\\] a\\\\b\\$c\\\"d%e)f\\[\\e[38;5;2m\\]\\[\\e[48;5;236m\\] same bg \\[\\e[38;5;236m\\]\\[\\e[48;5;124m\\]\u{e0c6}\\[\\e[38;5;231m\\]\\[\\e[48;5;124m\\] 1 \\[\\e[38;5;124m\\]\\[\\e[49m\\]\u{e0b0}\\[\\e[39m\\]"),
+            34
+        ));
+    }
+
+    #[test]
+    fn bash_rtl() {
+        assert_eq!(snapshot(Shell::Bash, true), (
+            String::from("\\[\\e[38;5;124m\\]\u{e0b2}\\[\\e[38;5;231m\\]\\[\\e[48;5;124m\\] 1 \\[\\e[49m\\]\\[\\e[39m\\]\\[\\e[38;5;236m\\]\\[\\e[48;5;124m\\]\u{e0b2}\\[\\e[38;5;2m\\]\\[\\e[48;5;236m\\] same bg \\[\\e[49m\\]\\[\\e[39m\\]\\[\\e[38;5;252m\\]\\[\\e[48;5;236m\\] a\\\\b\\$c\\\"d%e)f \\[\\e[49m\\]\\[\\e[39m\\]\\[\\e[38;5;31m\\]\\[\\e[48;5;236m\\]\u{e0b2}\\[\\e[38;5;15m\\]\\[\\e[48;5;31m\\] user \\[\\e[49m\\]\\[\\e[39m\\]"),
+            34
+        ));
+    }
+
+    #[test]
+    fn zsh_ltr() {
+        assert_eq!(snapshot(Shell::Zsh, false), (
+            String::from("%{\u{1b}[38;5;31m%}\u{e0b6}%{\u{1b}[38;5;15m%}%{\u{1b}[48;5;31m%} %{\u{1b}[1m%}user%{\u{1b}[22m%} %{\u{1b}[38;5;31m%}%{\u{1b}[48;5;236m%}\u{e0c6}%{\u{1b}[38;5;252m%}%{\u{1b}[48;5;236m%} a\\b$c\"d%%e%)f%{\u{1b}[38;5;2m%}%{\u{1b}[48;5;236m%} same bg %{\u{1b}[38;5;236m%}%{\u{1b}[48;5;124m%}\u{e0c6}%{\u{1b}[38;5;231m%}%{\u{1b}[48;5;124m%} 1 %{\u{1b}[38;5;124m%}%{\u{1b}[49m%}\u{e0b0}%{\u{1b}[39m%}"),
+            34
+        ));
+    }
+
+    #[test]
+    fn zsh_rtl() {
+        assert_eq!(snapshot(Shell::Zsh, true), (
+            String::from("%{\u{1b}[38;5;124m%}\u{e0b2}%{\u{1b}[38;5;231m%}%{\u{1b}[48;5;124m%} 1 %{\u{1b}[49m%}%{\u{1b}[39m%}%{\u{1b}[38;5;236m%}%{\u{1b}[48;5;124m%}\u{e0b2}%{\u{1b}[38;5;2m%}%{\u{1b}[48;5;236m%} same bg %{\u{1b}[49m%}%{\u{1b}[39m%}%{\u{1b}[38;5;252m%}%{\u{1b}[48;5;236m%} a\\b$c\"d%%e%)f %{\u{1b}[49m%}%{\u{1b}[39m%}%{\u{1b}[38;5;31m%}%{\u{1b}[48;5;236m%}\u{e0b2}%{\u{1b}[38;5;15m%}%{\u{1b}[48;5;31m%} user %{\u{1b}[49m%}%{\u{1b}[39m%}"),
+            34
+        ));
+    }
+
+    #[test]
+    fn width() {
+        assert_eq!(visible_width(Shell::Bare, "\x1b]8;;file:///\x07\x1b[1mab\x1b[22m\x1b]8;;\x1b\\"), 2);
+        assert_eq!(visible_width(Shell::Bash, "\\[\\e[1m\\]\\u@\\h\\[\\e[22m\\]"), 3);
+        assert_eq!(visible_width(Shell::Zsh, "%{\x1b[1m%}%n%%%{\x1b[22m%}"), 2);
+        assert_eq!(visible_width(Shell::Bare, "日本"), 4);
+    }
+}
@@ -1,38 +1,93 @@
+pub mod segment_battery;
+pub mod segment_cloud;
+pub mod segment_container;
+pub mod segment_custom;
 pub mod segment_cwd;
+pub mod segment_disk;
+pub mod segment_duration;
+pub mod segment_env;
 pub mod segment_host;
 pub mod segment_jobs;
+pub mod segment_kube;
+pub mod segment_load;
+pub mod segment_mem;
 pub mod segment_nix;
 pub mod segment_perms;
+pub mod segment_prompt;
 pub mod segment_ps;
 pub mod segment_root;
 pub mod segment_ssh;
 pub mod segment_time;
+pub mod segment_toolchain;
 pub mod segment_user;
 pub mod segment_virtualenv;
 pub mod segment_linebreak;
 
+pub use self::segment_battery::*;
+pub use self::segment_cloud::*;
+pub use self::segment_container::*;
+pub use self::segment_custom::*;
 pub use self::segment_cwd::*;
+pub use self::segment_disk::*;
+pub use self::segment_duration::*;
+pub use self::segment_env::*;
 pub use self::segment_host::*;
 pub use self::segment_jobs::*;
+pub use self::segment_kube::*;
+pub use self::segment_load::*;
+pub use self::segment_mem::*;
 pub use self::segment_nix::*;
 pub use self::segment_perms::*;
+pub use self::segment_prompt::*;
 pub use self::segment_ps::*;
 pub use self::segment_root::*;
 pub use self::segment_ssh::*;
 pub use self::segment_time::*;
+pub use self::segment_toolchain::*;
 pub use self::segment_user::*;
 pub use self::segment_virtualenv::*;
 pub use self::segment_linebreak::*;
 
 #[cfg(feature = "git2")] pub mod segment_git;
 #[cfg(feature = "git2")] pub use self::segment_git::*;
+#[cfg(feature = "serde_json")] pub mod segment_plugin;
+#[cfg(feature = "serde_json")] pub use self::segment_plugin::*;
 
 
 use crate::Shell;
 use crate::format::*;
-use std::borrow::Cow;
+use std::{borrow::Cow, env, fmt, path::PathBuf};
 use crate::theme::Theme;
 
+/// Looks for a file in the current directory and all of its parents
+pub fn find_in_ancestors(name: &str) -> Option<PathBuf> {
+    let cwd = env::current_dir().ok()?;
+    cwd.ancestors()
+        .map(|dir| dir.join(name))
+        .find(|path| path.exists())
+}
+
+/// Looks up a key in an INI style file, like ~/.aws/config. Also good
+/// enough for simple TOML files, though the value keeps its quotes.
+pub fn ini_value(content: &str, section: &str, key: &str) -> Option<String> {
+    let mut in_section = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            in_section = name.trim() == section;
+        } else if in_section {
+            let mut parts = line.splitn(2, '=');
+            if parts.next().map(str::trim) == Some(key) {
+                return parts.next().map(|value| value.trim().to_string());
+            }
+        }
+    }
+    None
+}
+
 pub struct Segment {
     pub bg: u8,
     pub fg: u8,
@@ -44,7 +99,8 @@ pub struct Segment {
     bold: bool,
 
     escaped: bool,
-    text: Cow<'static, str>
+    text: Cow<'static, str>,
+    link: Option<String>
 }
 impl Segment {
     pub fn new<S>(bg: u8, fg: u8, text: S) -> Self
@@ -61,7 +117,8 @@ impl Segment {
             bold: false,
 
             escaped: false,
-            text:  text.into()
+            text:  text.into(),
+            link: None
         }
     }
     pub fn dont_escape(mut self) -> Self {
@@ -91,6 +148,28 @@ impl Segment {
         self.no_space_after = true;
         self
     }
+    /// Makes the text an OSC 8 hyperlink to the URL
+    pub fn with_link(mut self, url: String) -> Self {
+        self.link = Some(url);
+        self
+    }
+    fn render_text<W: fmt::Write>(&self, out: &mut W, shell: Shell, bold: bool) -> fmt::Result {
+        if let Some(ref url) = self.link {
+            // The URL is never marked as escaped, unlike the text
+            let mut url = url.clone();
+            escape(shell, &mut url);
+            write!(out, "{}", osc(shell, &format!("8;;{}", url)))?;
+        }
+        if bold {
+            write!(out, "{}", as_bold(shell, &self.text))?;
+        } else {
+            write!(out, "{}", self.text)?;
+        }
+        if self.link.is_some() {
+            write!(out, "{}", osc(shell, "8;;"))?;
+        }
+        Ok(())
+    }
     pub fn escape(&mut self, shell: Shell) {
         if self.escaped {
             return;
@@ -98,43 +177,40 @@ impl Segment {
         escape(shell, self.text.to_mut());
         self.escaped = true;
     }
-    pub fn print(&self, next: Option<&Segment>, shell: Shell, _theme: &Theme) {
-        print!("{}{}{} ", self.before, fg(shell, self.fg), bg(shell, self.bg));
+    pub fn render<W: fmt::Write>(&self, out: &mut W, next: Option<&Segment>, shell: Shell, _theme: &Theme) -> fmt::Result {
+        write!(out, "{}{}{} ", self.before, fg(shell, self.fg), bg(shell, self.bg))?;
 
-        if self.bold {
-            print!("{}", as_bold(shell, &self.text));
-        } else {
-            print!("{}", self.text);
-        }
+        self.render_text(out, shell, self.bold)?;
 
         if !self.no_space_after && (next.map(|n| n.bg != self.bg).unwrap_or(true)) {
-            print!(" ")
+            write!(out, " ")?;
         }
         match next {
             Some(next) if next.is_conditional() => {},
-            Some(next) if next.bg == self.bg => {}, //print!("{}\u{e0b5}", fg(shell, theme.separator_fg)),
-            Some(next) if self.bg == 0 => print!("{}{}\u{e0c6}", fg(shell, next.bg), bg(shell, next.bg)),
-            Some(next) => print!("{}{}\u{e0c6}", fg(shell, self.bg), bg(shell, next.bg)),
+            Some(next) if next.bg == self.bg => {}, //write!(out, "{}\u{e0b5}", fg(shell, theme.separator_fg)),
+            Some(next) if self.bg == 0 => write!(out, "{}{}\u{e0c6}", fg(shell, next.bg), bg(shell, next.bg))?,
+            Some(next) => write!(out, "{}{}\u{e0c6}", fg(shell, self.bg), bg(shell, next.bg))?,
             // Last tile resets colors
-            None       => print!("{}{}\u{e0b0}{}", fg(shell, self.bg), reset(shell, false), reset(shell, true))
+            None       => write!(out, "{}{}\u{e0b0}{}", fg(shell, self.bg), reset(shell, false), reset(shell, true))?
         }
-        print!("{}", self.after);
+        write!(out, "{}", self.after)
     }
-    pub fn print_rtl(&self, next: Option<&Segment>, shell: Shell, _theme: &Theme) {
-        // Here, next is going leftwards - see how this func is called in main.rs .
-        print!("{}", self.after);
+    pub fn render_rtl<W: fmt::Write>(&self, out: &mut W, next: Option<&Segment>, shell: Shell, _theme: &Theme) -> fmt::Result {
+        // Here, next is going leftwards - see how this func is called in render.rs .
+        write!(out, "{}", self.after)?;
         match next {
             Some(next) if next.is_conditional() => {},
             Some(next) if next.bg == self.bg => {},
-                // print!("{}{}", fg(shell, theme.separator_fg), bg(shell, self.bg)),
-            Some(next) => print!("{}{}",  fg(shell, self.bg), bg(shell, next.bg)),
-            None       => print!("{}", fg(shell, self.bg))
+                // write!(out, "{}{}", fg(shell, theme.separator_fg), bg(shell, self.bg)),
+            Some(next) => write!(out, "{}{}",  fg(shell, self.bg), bg(shell, next.bg))?,
+            None       => write!(out, "{}", fg(shell, self.bg))?
         }
-        print!("{}{} {}", fg(shell, self.fg), bg(shell, self.bg), self.text);
+        write!(out, "{}{} ", fg(shell, self.fg), bg(shell, self.bg))?;
+        self.render_text(out, shell, false)?;
 
         if !self.no_space_after {
-            print!(" ")
+            write!(out, " ")?;
         }
-        print!("{}{}{}", reset(shell, false), reset(shell, true), self.before);
+        write!(out, "{}{}{}", reset(shell, false), reset(shell, true), self.before)
     }
 }
@@ -0,0 +1,131 @@
+use std::{fs, path::Path};
+use crate::{Powerline, Segment};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Status {
+    Charging,
+    Discharging,
+    Other
+}
+
+fn read(dir: &Path, name: &str) -> Option<String> {
+    fs::read_to_string(dir.join(name)).ok().map(|value| value.trim().to_string())
+}
+
+/// Reads the combined capacity and status of all system batteries, ignoring
+/// things like wireless mice that also report a battery
+fn battery(sysfs: &Path) -> Option<(u8, Status)> {
+    let mut dirs: Vec<_> = fs::read_dir(sysfs.join("class").join("power_supply")).ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|dir| read(dir, "type").as_deref() == Some("Battery"))
+        .filter(|dir| read(dir, "scope").as_deref() != Some("Device"))
+        .collect();
+    dirs.sort();
+
+    let mut total = 0u32;
+    let mut count = 0u32;
+    let mut status = Status::Other;
+    for dir in dirs {
+        let capacity: u32 = match read(&dir, "capacity").and_then(|capacity| capacity.parse().ok()) {
+            Some(capacity) => capacity,
+            None => continue
+        };
+        total += capacity.min(100);
+        count += 1;
+        match read(&dir, "status").as_deref() {
+            Some("Charging") => status = Status::Charging,
+            Some("Discharging") if status != Status::Charging => status = Status::Discharging,
+            _ => ()
+        }
+    }
+    if count == 0 {
+        return None;
+    }
+    Some(((total / count) as u8, status))
+}
+
+pub fn segment_battery(p: &mut Powerline, sysfs: &Path, threshold: u8) {
+    let (capacity, status) = match battery(sysfs) {
+        Some(battery) => battery,
+        None => return
+    };
+    if capacity > threshold {
+        return;
+    }
+
+    let t = &p.theme;
+    let (bg, fg) = if status == Status::Charging {
+        (t.battery_bg, t.battery_fg)
+    } else if capacity <= t.battery_critical_level {
+        (t.battery_critical_bg, t.battery_critical_fg)
+    } else if capacity <= t.battery_warning_level {
+        (t.battery_warning_bg, t.battery_warning_fg)
+    } else {
+        (t.battery_bg, t.battery_fg)
+    };
+    let text = match status {
+        Status::Charging => format!("{} {}%", t.battery_charging_char, capacity),
+        Status::Discharging => format!("{} {}%", t.battery_discharging_char, capacity),
+        Status::Other => format!("{}%", capacity)
+    };
+    p.segments.push(Segment::new(bg, fg, text));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util::TempDir, theme, Shell};
+
+    /// Creates a fake sysfs with the given power supplies and their files
+    fn fixture(supplies: &[(&str, &[(&str, &str)])]) -> TempDir {
+        let root = TempDir::new("battery");
+        for (supply, files) in supplies {
+            let dir = Path::new("class").join("power_supply").join(supply);
+            for (file, content) in *files {
+                root.write(dir.join(file), &format!("{}\n", content));
+            }
+        }
+        root
+    }
+
+    fn segment(root: &Path, threshold: u8) -> Option<(String, u8)> {
+        let mut p = Powerline::new(theme::DEFAULT, Shell::Bare);
+        segment_battery(&mut p, root, threshold);
+        p.segments.pop().map(|segment| (segment.text.into_owned(), segment.bg))
+    }
+
+    #[test]
+    fn discharging() {
+        let root = fixture(&[
+            ("AC", &[("type", "Mains"), ("online", "0")]),
+            ("BAT0", &[("type", "Battery"), ("capacity", "15"), ("status", "Discharging")]),
+            ("hidpp_battery_0", &[("type", "Battery"), ("scope", "Device"), ("capacity", "90"), ("status", "Discharging")])
+        ]);
+        let t = theme::DEFAULT;
+        assert_eq!(segment(&root, 100), Some((format!("{} 15%", t.battery_discharging_char), t.battery_warning_bg)));
+    }
+
+    #[test]
+    fn charging_and_averaged() {
+        let root = fixture(&[
+            ("BAT0", &[("type", "Battery"), ("capacity", "4"), ("status", "Charging")]),
+            ("BAT1", &[("type", "Battery"), ("capacity", "10"), ("status", "Unknown")])
+        ]);
+        let t = theme::DEFAULT;
+        assert_eq!(segment(&root, 100), Some((format!("{} 7%", t.battery_charging_char), t.battery_bg)));
+    }
+
+    #[test]
+    fn above_threshold() {
+        let root = fixture(&[
+            ("BAT0", &[("type", "Battery"), ("capacity", "80"), ("status", "Full")])
+        ]);
+        assert_eq!(segment(&root, 50), None);
+    }
+
+    #[test]
+    fn no_battery() {
+        let root = fixture(&[("AC", &[("type", "Mains")])]);
+        assert_eq!(segment(&root, 100), None);
+    }
+}
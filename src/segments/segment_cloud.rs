@@ -0,0 +1,231 @@
+use std::{env, fs, path::PathBuf, time::{Duration, SystemTime, UNIX_EPOCH}};
+use crate::{segments, Powerline, Segment};
+
+/// Parses an RFC 3339 timestamp like 2019-11-02T15:04:05Z or
+/// 2019-11-02T17:04:05.123+02:00, which is what the AWS tools write.
+/// Fractional seconds are ignored.
+fn parse_timestamp(timestamp: &str) -> Option<SystemTime> {
+    let number = |range: std::ops::Range<usize>| -> Option<i64> { timestamp.get(range)?.parse().ok() };
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, min, sec) = (number(11..13)?, number(14..16)?, number(17..19)?);
+
+    let rest = timestamp.get(19..)?;
+    let zone = rest.trim_start_matches(|c: char| c == '.' || c.is_ascii_digit());
+    let offset = match zone.chars().next() {
+        None | Some('Z') | Some('z') => 0,
+        Some(sign @ '+') | Some(sign @ '-') => {
+            let digits = zone[1..].replace(':', "");
+            let (hours, mins): (i64, i64) = (digits.get(0..2)?.parse().ok()?, digits.get(2..4)?.parse().ok()?);
+            let offset = hours * 3600 + mins * 60;
+            if sign == '-' { -offset } else { offset }
+        },
+        Some(_) => return None
+    };
+
+    // Days since the epoch in the proleptic Gregorian calendar
+    let (y, m) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * m + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+
+    let secs = days * 86400 + hour * 3600 + min * 60 + sec - offset;
+    Some(UNIX_EPOCH + Duration::from_secs(secs.max(0) as u64))
+}
+
+fn aws_config_path() -> Option<PathBuf> {
+    env::var_os("AWS_CONFIG_FILE")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".aws").join("config")))
+}
+
+/// Finds when the SSO login of a profile runs out, from the token cache the
+/// AWS CLI keeps
+#[cfg(feature = "serde_json")]
+fn aws_sso_expiry(config: &str, section: &str) -> Option<SystemTime> {
+    let start_url = segments::ini_value(config, section, "sso_start_url").or_else(|| {
+        let session = segments::ini_value(config, section, "sso_session")?;
+        segments::ini_value(config, &format!("sso-session {}", session), "sso_start_url")
+    })?;
+
+    let cache = dirs::home_dir()?.join(".aws").join("sso").join("cache");
+    fs::read_dir(cache).ok()?
+        .filter_map(|entry| fs::read_to_string(entry.ok()?.path()).ok())
+        .filter_map(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .filter(|token| token["startUrl"].as_str() == Some(&start_url))
+        .filter_map(|token| parse_timestamp(token["expiresAt"].as_str()?))
+        .max()
+}
+
+/// Finds when the temporary credentials of a profile that assumes a role
+/// run out, from the credential cache the AWS CLI keeps in `cache`
+#[cfg(feature = "serde_json")]
+fn aws_role_expiry(config: &str, section: &str, cache: &std::path::Path) -> Option<SystemTime> {
+    // The cache files are named after a hash of the request, so match them
+    // by the role instead: arn:aws:iam::<account>:role/<path>/<name> shows up
+    // as arn:aws:sts::<account>:assumed-role/<name>/<session>
+    let role_arn = segments::ini_value(config, section, "role_arn")?;
+    let colon = role_arn.find(":role/")?;
+    let name = role_arn[colon + ":role/".len()..].rsplit('/').next()?;
+    let assumed = format!("{}:assumed-role/{}/", role_arn[..colon].replacen(":iam:", ":sts:", 1), name);
+
+    fs::read_dir(cache).ok()?
+        .filter_map(|entry| fs::read_to_string(entry.ok()?.path()).ok())
+        .filter_map(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .filter(|cached| cached["AssumedRoleUser"]["Arn"].as_str().is_some_and(|arn| arn.starts_with(&assumed)))
+        .filter_map(|cached| parse_timestamp(cached["Credentials"]["Expiration"].as_str()?))
+        .max()
+}
+
+fn segment_aws(p: &mut Powerline) {
+    let profile = match env::var("AWS_PROFILE").or_else(|_| env::var("AWS_DEFAULT_PROFILE")) {
+        Ok(ref profile) if !profile.is_empty() => profile.clone(),
+        _ => return
+    };
+    let section = if profile == "default" { profile.clone() } else { format!("profile {}", profile) };
+    let config = aws_config_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default();
+
+    let region = env::var("AWS_REGION")
+        .or_else(|_| env::var("AWS_DEFAULT_REGION"))
+        .ok()
+        .filter(|region| !region.is_empty())
+        .or_else(|| segments::ini_value(&config, &section, "region"));
+
+    // Tools like aws-vault export when the temporary credentials expire
+    let expiry = env::var("AWS_SESSION_EXPIRATION")
+        .or_else(|_| env::var("AWS_CREDENTIAL_EXPIRATION"))
+        .ok()
+        .and_then(|timestamp| parse_timestamp(&timestamp));
+    #[cfg(feature = "serde_json")]
+    let expiry = expiry
+        .or_else(|| aws_role_expiry(&config, &section, &dirs::home_dir()?.join(".aws").join("cli").join("cache")))
+        .or_else(|| aws_sso_expiry(&config, &section));
+
+    let mut text = match region {
+        Some(region) => format!("{}/{}", profile, region),
+        None => profile
+    };
+    let (mut bg, mut fg) = (p.theme.aws_bg, p.theme.aws_fg);
+    if let Some(expiry) = expiry {
+        match expiry.duration_since(SystemTime::now()) {
+            Ok(left) => text = format!("{} {}", text, segments::humanize(left)),
+            Err(_) => {
+                bg = p.theme.aws_expired_bg;
+                fg = p.theme.aws_expired_fg;
+            }
+        }
+    }
+    p.segments.push(Segment::new(bg, fg, text));
+}
+
+fn segment_gcp(p: &mut Powerline) {
+    let dir = match env::var_os("CLOUDSDK_CONFIG") {
+        Some(dir) => PathBuf::from(dir),
+        None => match dirs::home_dir() {
+            Some(home) => home.join(".config").join("gcloud"),
+            None => return
+        }
+    };
+    let name = match env::var("CLOUDSDK_ACTIVE_CONFIG_NAME") {
+        Ok(ref name) if !name.is_empty() => name.clone(),
+        _ => match fs::read_to_string(dir.join("active_config")) {
+            Ok(name) => name.trim().to_string(),
+            Err(_) => return
+        }
+    };
+    if name.is_empty() {
+        return;
+    }
+
+    let project = env::var("CLOUDSDK_CORE_PROJECT").ok()
+        .filter(|project| !project.is_empty())
+        .or_else(|| {
+            let config = fs::read_to_string(dir.join("configurations").join(format!("config_{}", name))).ok()?;
+            segments::ini_value(&config, "core", "project")
+        });
+
+    let text = match project {
+        Some(project) => format!("{}/{}", name, project),
+        None => name
+    };
+    p.segments.push(Segment::new(p.theme.gcp_bg, p.theme.gcp_fg, text));
+}
+
+#[cfg(feature = "serde_json")]
+fn segment_azure(p: &mut Powerline) {
+    let dir = match env::var_os("AZURE_CONFIG_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => match dirs::home_dir() {
+            Some(home) => home.join(".azure"),
+            None => return
+        }
+    };
+    let content = match fs::read_to_string(dir.join("azureProfile.json")) {
+        Ok(content) => content,
+        Err(_) => return
+    };
+    // The Azure CLI writes this file with a byte order mark
+    let profile: serde_json::Value = match serde_json::from_str(content.trim_start_matches('\u{feff}')) {
+        Ok(profile) => profile,
+        Err(_) => return
+    };
+
+    let name = profile["subscriptions"].as_array()
+        .and_then(|subscriptions| subscriptions.iter().find(|sub| sub["isDefault"].as_bool() == Some(true)))
+        .and_then(|sub| sub["name"].as_str());
+    if let Some(name) = name {
+        p.segments.push(Segment::new(p.theme.azure_bg, p.theme.azure_fg, name.to_string()));
+    }
+}
+
+pub fn segment_cloud(p: &mut Powerline) {
+    segment_aws(p);
+    segment_gcp(p);
+    #[cfg(feature = "serde_json")]
+    segment_azure(p);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "serde_json")] use crate::test_util::TempDir;
+
+    fn timestamp(secs: u64) -> Option<SystemTime> {
+        Some(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), timestamp(0));
+        assert_eq!(parse_timestamp("2019-11-02T15:04:05Z"), timestamp(1572707045));
+        assert_eq!(parse_timestamp("2019-11-02T15:04:05.123456Z"), timestamp(1572707045));
+        assert_eq!(parse_timestamp("2019-11-02T15:04:05"), timestamp(1572707045));
+        assert_eq!(parse_timestamp("2019-11-02T17:04:05+02:00"), timestamp(1572707045));
+        assert_eq!(parse_timestamp("2019-11-02T10:04:05.5-0500"), timestamp(1572707045));
+        assert_eq!(parse_timestamp("2019-11-02T15:04:05 UTC"), None);
+        assert_eq!(parse_timestamp("2019-11-02"), None);
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn role_expiry() {
+        let cache = TempDir::new("cloud");
+        let cached = |file: &str, arn: &str, expiration: &str| {
+            let json = format!(
+                r#"{{"Credentials": {{"AccessKeyId": "AKIA", "Expiration": "{}"}}, "AssumedRoleUser": {{"Arn": "{}"}}}}"#,
+                expiration, arn
+            );
+            cache.write(file, &json);
+        };
+        cached("1.json", "arn:aws:sts::123456789012:assumed-role/Admin/botocore-session-1", "2019-11-02T15:04:05+00:00");
+        cached("2.json", "arn:aws:sts::123456789012:assumed-role/Admin/botocore-session-2", "2019-11-02T16:04:05+00:00");
+        cached("3.json", "arn:aws:sts::123456789012:assumed-role/ReadOnly/botocore-session-3", "2019-11-02T17:04:05+00:00");
+
+        let config = "[profile admin]\nrole_arn = arn:aws:iam::123456789012:role/team/Admin\n[profile sso]\nsso_account_id = 1\n";
+        assert_eq!(aws_role_expiry(config, "profile admin", &cache), timestamp(1572707045 + 3600));
+        assert_eq!(aws_role_expiry(config, "profile sso", &cache), None);
+    }
+}
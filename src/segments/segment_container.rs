@@ -0,0 +1,96 @@
+use std::{env, fs, path::Path};
+use crate::{segments, Powerline, Segment};
+
+/// Reads a field like `name="fedora-toolbox-39"` from /run/.containerenv
+fn containerenv_field(content: &str, field: &str) -> Option<String> {
+    content.lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(2, '=');
+            if parts.next()?.trim() == field { parts.next() } else { None }
+        })
+        .map(|value| value.trim().trim_matches('"').to_string())
+        .find(|value| !value.is_empty())
+}
+
+/// What kind of container the cgroups of the init process, from
+/// /proc/1/cgroup, say we're in. This only works with cgroups v1, as v2
+/// hides the path.
+fn cgroup_kind(cgroup: &str) -> Option<&'static str> {
+    cgroup.lines()
+        .filter_map(|line| line.splitn(3, ':').nth(2))
+        .find_map(|path| if path.contains("kubepods") {
+            Some("kubernetes")
+        } else if path.contains("/docker") {
+            Some("docker")
+        } else if path.contains("/lxc") {
+            Some("lxc")
+        } else {
+            None
+        })
+}
+
+/// The name of the container, or what kind it is if it has no name
+fn container(proc: &Path) -> Option<String> {
+    // Set by distrobox
+    if let Ok(name) = env::var("CONTAINER_ID") {
+        if !name.is_empty() {
+            return Some(name);
+        }
+    }
+    // Written by podman, which toolbox is built on
+    if let Ok(content) = fs::read_to_string("/run/.containerenv") {
+        return containerenv_field(&content, "name").or_else(|| Some(String::from(
+            if Path::new("/run/.toolboxenv").exists() { "toolbox" } else { "podman" }
+        )));
+    }
+    if Path::new("/.dockerenv").exists() {
+        return Some(String::from("docker"));
+    }
+
+    // Set for the init process of most other containers, and by systemd for
+    // everyone else
+    let kind = env::var("container").ok()
+        .or_else(|| fs::read_to_string("/run/systemd/container").ok())
+        .map(|kind| kind.trim().to_string())
+        .filter(|kind| !kind.is_empty());
+    match kind.as_deref() {
+        // Machines are known by their host name
+        Some("systemd-nspawn") => segments::hostname().or(kind),
+        Some(_) => kind,
+        None => fs::read_to_string(proc.join("1").join("cgroup")).ok()
+            .and_then(|cgroup| cgroup_kind(&cgroup))
+            .map(String::from)
+    }
+}
+
+pub fn segment_container(p: &mut Powerline, proc: &Path) {
+    if let Some(name) = container(proc) {
+        p.segments.push(Segment::new(p.theme.container_bg, p.theme.container_fg, name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn containerenv() {
+        let content = "engine=\"podman-4.7.0\"\nname=\"fedora-toolbox-39\"\nid=\"4a5b\"\nimage=\"registry.fedoraproject.org/fedora-toolbox:39\"\nrootless=1\n";
+        assert_eq!(containerenv_field(content, "name"), Some(String::from("fedora-toolbox-39")));
+        assert_eq!(containerenv_field(content, "rootless"), Some(String::from("1")));
+        // Rootful podman leaves the file empty
+        assert_eq!(containerenv_field("", "name"), None);
+        assert_eq!(containerenv_field("name=\"\"\n", "name"), None);
+    }
+
+    #[test]
+    fn cgroups() {
+        let docker = "12:pids:/docker/3f4e2a\n11:memory:/docker/3f4e2a\n0::/system.slice/containerd.service\n";
+        assert_eq!(cgroup_kind(docker), Some("docker"));
+        assert_eq!(cgroup_kind("10:cpu,cpuacct:/kubepods/burstable/pod1234/abcd\n"), Some("kubernetes"));
+        assert_eq!(cgroup_kind("5:devices:/lxc/web\n"), Some("lxc"));
+        // The host, and cgroups v2
+        assert_eq!(cgroup_kind("12:pids:/init.scope\n1:name=systemd:/init.scope\n"), None);
+        assert_eq!(cgroup_kind("0::/\n"), None);
+    }
+}
@@ -0,0 +1,41 @@
+use std::time::Duration;
+use crate::{command, segments, Powerline, Segment};
+
+pub fn segment_custom(p: &mut Powerline) {
+    // Start all commands before waiting on any of them, so the prompt is
+    // never held up for longer than the longest timeout.
+    let mut running = Vec::with_capacity(p.theme.custom.len());
+
+    for entry in &p.theme.custom {
+        let command = match entry.command {
+            Some(ref command) => command,
+            None => continue
+        };
+        if let Some(ref file) = entry.if_exists {
+            if segments::find_in_ancestors(file).is_none() {
+                continue;
+            }
+        }
+
+        let timeout = entry.timeout.map(Duration::from_millis).unwrap_or(command::DEFAULT_TIMEOUT);
+        if let Some(pending) = command::spawn(command, None, timeout) {
+            running.push((entry, pending));
+        }
+    }
+
+    for (entry, pending) in running {
+        let output = match pending.wait() {
+            Some(output) => output,
+            None => continue
+        };
+        let output = output.trim();
+        if output.is_empty() {
+            continue;
+        }
+        p.segments.push(Segment::new(
+            entry.bg.unwrap_or(p.theme.custom_bg),
+            entry.fg.unwrap_or(p.theme.custom_fg),
+            output.to_string()
+        ));
+    }
+}
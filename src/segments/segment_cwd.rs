@@ -1,5 +1,16 @@
-use crate::{format, Powerline, Segment, Shell};
-use std::{env, path::PathBuf};
+use crate::{format, segments, Powerline, Segment, Shell};
+use std::{env, path::{Path, PathBuf}};
+
+/// The full current directory, with the home directory replaced by ~
+pub fn cwd_display() -> String {
+    let path = env::current_dir().unwrap_or_else(|_| PathBuf::from("error"));
+    if let Some(home) = dirs::home_dir() {
+        if let Ok(rest) = path.strip_prefix(&home) {
+            return Path::new("~").join(rest).to_string_lossy().into_owned();
+        }
+    }
+    path.to_string_lossy().into_owned()
+}
 
 fn simple_cwd_string(shell: Shell, cwd_max_depth: u8) -> String {
     // are we in the home dir?
@@ -43,13 +54,20 @@ fn simple_cwd_string(shell: Shell, cwd_max_depth: u8) -> String {
         if next.is_none() {
             out += &format::as_bold(shell, name);
         } else {
-            out += &name;
+            out += name;
         }
     }
 
-    return out;
+    out
 }
 
-pub fn segment_cwd(p: &mut Powerline, cwd_max_depth: u8) {
-    p.segments.push(Segment::new(p.theme.path_bg, p.theme.path_fg, simple_cwd_string(p.shell, cwd_max_depth)).dont_escape());
+pub fn segment_cwd(p: &mut Powerline, cwd_max_depth: u8, hyperlink: bool) {
+    let mut segment = Segment::new(p.theme.path_bg, p.theme.path_fg, simple_cwd_string(p.shell, cwd_max_depth)).dont_escape();
+    if hyperlink {
+        if let Ok(cwd) = env::current_dir() {
+            let host = segments::hostname().unwrap_or_default();
+            segment = segment.with_link(format!("file://{}{}", host, format::percent_encode(&cwd.to_string_lossy())));
+        }
+    }
+    p.segments.push(segment);
 }
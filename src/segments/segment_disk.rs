@@ -0,0 +1,79 @@
+use std::{env, ffi::CString, mem, os::unix::ffi::OsStrExt};
+use crate::{Powerline, Segment};
+
+/// Formats a size in bytes like `df -h` does
+fn human_size(bytes: u64) -> String {
+    const UNITS: &[char] = &['K', 'M', 'G', 'T', 'P'];
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if size < 10.0 {
+        format!("{:.1}{}", size, UNITS[unit])
+    } else {
+        format!("{:.0}{}", size, UNITS[unit])
+    }
+}
+
+/// The free bytes and the percentage in use of the filesystem the current
+/// directory is on
+fn usage() -> Option<(u64, u32)> {
+    let cwd = CString::new(env::current_dir().ok()?.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { mem::zeroed() };
+    if unsafe { libc::statvfs(cwd.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let block = stat.f_frsize as u64;
+    // What unprivileged users can use, like df
+    let free = stat.f_bavail as u64 * block;
+    let used = (stat.f_blocks as u64).saturating_sub(stat.f_bfree as u64) * block;
+    // Like df, reserved blocks count as neither used nor free, and it rounds up
+    let usable = used + free;
+    if usable == 0 {
+        return None;
+    }
+    let percent = (used * 100).div_ceil(usable).min(100);
+    Some((free, percent as u32))
+}
+
+/// Shows how much space is left on the current filesystem, colored by how
+/// full it is. Nothing is shown below `threshold` percent used.
+pub fn segment_disk(p: &mut Powerline, threshold: u8) {
+    let (free, used) = match usage() {
+        Some(usage) => usage,
+        None => return
+    };
+    if used < u32::from(threshold) {
+        return;
+    }
+
+    let t = &p.theme;
+    let (bg, fg) = if used >= u32::from(t.disk_critical_level) {
+        (t.disk_critical_bg, t.disk_critical_fg)
+    } else if used >= u32::from(t.disk_warning_level) {
+        (t.disk_warning_bg, t.disk_warning_fg)
+    } else {
+        (t.disk_bg, t.disk_fg)
+    };
+    p.segments.push(Segment::new(bg, fg, format!("disk {}", human_size(free))));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(human_size(0), "0.0K");
+        assert_eq!(human_size(512), "0.5K");
+        assert_eq!(human_size(9 * 1024 + 900), "9.9K");
+        assert_eq!(human_size(10 * 1024), "10K");
+        assert_eq!(human_size(1023 * 1024), "1023K");
+        assert_eq!(human_size(1024 * 1024), "1.0M");
+        assert_eq!(human_size(150 * 1024 * 1024 * 1024), "150G");
+        assert_eq!(human_size(3 * 1024u64.pow(5)), "3.0P");
+        assert_eq!(human_size(2048 * 1024u64.pow(5)), "2048P");
+    }
+}
@@ -0,0 +1,22 @@
+use std::time::Duration;
+use crate::{Powerline, Segment};
+
+/// Formats a duration compactly, like 1m05s or 2h03m
+pub fn humanize(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0 => format!("{}ms", duration.subsec_millis()),
+        1..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        3600..=86399 => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d{:02}h", secs / 86400, secs % 86400 / 3600)
+    }
+}
+
+pub fn segment_duration(p: &mut Powerline, duration: Option<Duration>, min: Duration) {
+    if let Some(duration) = duration {
+        if duration >= min {
+            p.segments.push(Segment::new(p.theme.duration_bg, p.theme.duration_fg, humanize(duration)));
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use std::env;
+use crate::{glob, Powerline, Segment};
+
+pub fn segment_env(p: &mut Powerline) {
+    for entry in &p.theme.env {
+        let value = match env::var(entry.var()) {
+            Ok(value) => value,
+            Err(_) => continue
+        };
+        let show = match entry.show_if {
+            Some(ref patterns) => glob::matches_any(patterns, &value),
+            None => !value.is_empty()
+        };
+        if !show {
+            continue;
+        }
+
+        let pick = |colors: &[(String, u8)]| colors.iter()
+            .find(|(pattern, _)| glob::matches(pattern, &value))
+            .map(|&(_, color)| color);
+        let bg = pick(&entry.value_bg).or(entry.bg).unwrap_or(p.theme.env_bg);
+        let fg = pick(&entry.value_fg).or(entry.fg).unwrap_or(p.theme.env_fg);
+
+        let text = match entry.label {
+            Some(ref label) => format!("{} {}", label, value),
+            None => value
+        };
+        p.segments.push(Segment::new(bg, fg, text));
+    }
+}
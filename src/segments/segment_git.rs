@@ -1,5 +1,5 @@
 #[cfg(feature = "flame")] use flame;
-use crate::{Powerline, Segment};
+use crate::{format, Powerline, Segment};
 use git2::{BranchType, ObjectType, Repository, Status, StatusOptions, StatusShow};
 
 fn discover_if_none(git: &mut Option<Repository>) -> bool {
@@ -33,7 +33,50 @@ fn statuses_if_none(git: &Repository, statuses: &mut Option<Vec<Status>>) -> boo
     } else { true }
 }
 
-pub fn segment_git(p: &mut Powerline) {
+/// Splits a remote URL like `git@host:user/repo.git` or
+/// `https://host/user/repo` into the host and path.
+fn parse_remote(url: &str) -> Option<(&str, &str)> {
+    let (host, path) = if let Some(i) = url.find("://") {
+        let rest = &url[i + 3..];
+        let slash = rest.find('/')?;
+        let host = &rest[..slash];
+        // Only web ports are useful in a web link, not ssh ones
+        let host = if url.starts_with("http") { host } else { host.split(':').next()? };
+        (host, &rest[slash + 1..])
+    } else {
+        // scp-like syntax
+        let colon = url.find(':')?;
+        (&url[..colon], &url[colon + 1..])
+    };
+    let host = host.rsplit('@').next()?;
+    let path = path.trim_end_matches('/');
+    Some((host, path.strip_suffix(".git").unwrap_or(path)))
+}
+
+fn branch_link(git: &Repository, branch: &str, template: &str) -> Option<String> {
+    let remote = git.config()
+        .and_then(|config| config.get_string(&format!("branch.{}.remote", branch)))
+        .unwrap_or_else(|_| String::from("origin"));
+    let remote = git.find_remote(&remote).ok()?;
+    let (host, path) = parse_remote(remote.url()?)?;
+
+    Some(template
+        .replace("{host}", host)
+        .replace("{path}", path)
+        .replace("{branch}", &format::percent_encode(branch)))
+}
+
+/// Returns the short name of what HEAD points to, for things outside the
+/// segments that want the branch.
+pub fn branch_name(p: &mut Powerline) -> Option<String> {
+    if !discover_if_none(&mut p.git) {
+        return None;
+    }
+    let head = p.git.as_ref().unwrap().head().ok()?;
+    head.shorthand().map(String::from)
+}
+
+pub fn segment_git(p: &mut Powerline, link_template: Option<&str>) {
     #[cfg(feature = "flame")]
     let _guard = flame::start_guard("segment git");
 
@@ -55,18 +98,14 @@ pub fn segment_git(p: &mut Powerline) {
     let mut upstream = None;
     let mut detached = false;
 
-    for branch in branches.unwrap() {
-        if let Ok((branch, _)) = branch {
-            if branch.is_head() {
-                local    = branch.get().target();
-                upstream = branch.upstream().ok().and_then(|b| b.get().target());
-
-                if let Ok(name) = branch.name() {
-                    if let Some(name) = name {
-                        branch_name = Some(name.to_string());
-                        break;
-                    }
-                }
+    for (branch, _) in branches.unwrap().flatten() {
+        if branch.is_head() {
+            local    = branch.get().target();
+            upstream = branch.upstream().ok().and_then(|b| b.get().target());
+
+            if let Ok(Some(name)) = branch.name() {
+                branch_name = Some(name.to_string());
+                break;
             }
         }
     }
@@ -108,7 +147,16 @@ pub fn segment_git(p: &mut Powerline) {
         bg = p.theme.git_detached_bg;
         fg = p.theme.git_detached_fg;
     }
-    p.segments.push(Segment::new(bg, fg, String::from(" ") + &branch_name.unwrap()));
+    let branch_name = branch_name.unwrap();
+    let link = link_template
+        .filter(|_| !detached)
+        .and_then(|template| branch_link(git, &branch_name, template));
+
+    let mut segment = Segment::new(bg, fg, String::from(" ") + &branch_name);
+    if let Some(link) = link {
+        segment = segment.with_link(link);
+    }
+    p.segments.push(segment);
 
     #[cfg(feature = "flame")]
     let _guard = flame::start_guard("checking remotes");
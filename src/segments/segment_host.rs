@@ -1,35 +1,144 @@
 use std::{
-    borrow::Cow,
     os::raw::{c_char, c_int},
     str
 };
-use crate::{Powerline, Segment, Shell};
+use crate::{glob, segments::UserHostOptions, theme::Theme, Powerline, Segment, Shell};
 
 extern "C" {
     fn gethostname(buf: *mut c_char, len: usize) -> c_int;
 }
 
-pub fn segment_host(p: &mut Powerline) {
-    let (bg, fg) = (p.theme.hostname_bg, p.theme.hostname_fg);
+pub fn hostname() -> Option<String> {
+    let mut name = [0u8; 256];
+    if unsafe { gethostname(&mut name[0] as *mut _ as *mut c_char, name.len()) } != 0 {
+        return None;
+    }
+    let len = name.iter().position(|i| *i == 0).unwrap_or(name.len());
+    str::from_utf8(&name[..len]).ok().map(String::from)
+}
 
-    if p.shell == Shell::Bare {
-        // We don't want to dont_escape() here
-        let mut name = [0u8; 256];
-        let mut string = Cow::from("error");
-        if unsafe { gethostname(&mut name[0] as *mut _ as *mut c_char, name.len()) } == 0 {
-            let len = name.iter().position(|i| *i == 0).unwrap_or(name.len());
+/// Whether the theme changes how host names look, in which case we have to
+/// find it ourselves instead of leaving it to the shell
+pub fn host_customized(theme: &Theme) -> bool {
+    !theme.hostname_palette.is_empty() || !theme.hostname_strip.is_empty() || !theme.hosts.is_empty()
+}
 
-            if let Ok(name) = str::from_utf8(&name[..len]) {
-                string = Cow::from(name.replace(".local", ""));
+fn shorten<'a>(theme: &Theme, name: &'a str) -> &'a str {
+    if theme.hostname_strip.is_empty() {
+        return name.strip_suffix(".local").unwrap_or(name);
+    }
+    for suffix in &theme.hostname_strip {
+        if suffix == ".*" {
+            if let Some(dot) = name.find('.') {
+                return &name[..dot];
+            }
+        } else if let Some(short) = name.strip_suffix(&**suffix) {
+            if !short.is_empty() {
+                return short;
             }
         }
-        p.segments.push(Segment::new(bg, fg, string));
+    }
+    name
+}
+
+/// FNV-1a, which is stable across versions unlike the std hasher
+fn hash(text: &str) -> u32 {
+    text.bytes().fold(0x811c_9dc5, |hash, byte| (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193))
+}
+
+/// The text and colors to show for a host name
+pub fn host_style(theme: &Theme, name: &str) -> (String, u8, u8) {
+    let entry = theme.hosts.iter().find(|entry| glob::matches(entry.name(), name));
+    let text = entry.and_then(|entry| entry.alias.clone())
+        .unwrap_or_else(|| shorten(theme, name).to_string());
+    let palette = &theme.hostname_palette;
+    let bg = entry.and_then(|entry| entry.bg)
+        .or_else(|| if palette.is_empty() { None } else { Some(palette[hash(name) as usize % palette.len()]) })
+        .unwrap_or(theme.hostname_bg);
+    let fg = entry.and_then(|entry| entry.fg).unwrap_or(theme.hostname_fg);
+    (text, bg, fg)
+}
+
+pub fn segment_host(p: &mut Powerline, options: &UserHostOptions, visible: bool) {
+    // In combined mode, the user module shows the host
+    if options.combined || !visible {
+        return;
+    }
+    if p.shell == Shell::Bare || host_customized(&p.theme) {
+        // We don't want to dont_escape() here
+        let (text, bg, fg) = match hostname() {
+            Some(name) => host_style(&p.theme, &name),
+            None => (String::from("error"), p.theme.hostname_bg, p.theme.hostname_fg)
+        };
+        p.segments.push(Segment::new(bg, fg, text));
         return;
     }
 
+    let (bg, fg) = (p.theme.hostname_bg, p.theme.hostname_fg);
     p.segments.push(Segment::new(bg, fg, match p.shell {
         Shell::Bare => unreachable!(),
         Shell::Bash => "\\h",
         Shell::Zsh  => "%m"
     }).dont_escape());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::{self, HostEntry};
+
+    fn strip(suffixes: &[&str]) -> Theme {
+        let mut theme = theme::DEFAULT;
+        theme.hostname_strip = suffixes.iter().map(|suffix| suffix.to_string()).collect();
+        theme
+    }
+
+    #[test]
+    fn domains() {
+        assert_eq!(shorten(&theme::DEFAULT, "laptop.local"), "laptop");
+        assert_eq!(shorten(&theme::DEFAULT, "web1.example.com"), "web1.example.com");
+
+        let theme = strip(&[".example.com", ".corp"]);
+        assert_eq!(shorten(&theme, "web1.example.com"), "web1");
+        assert_eq!(shorten(&theme, "db.eu.corp"), "db.eu");
+        assert_eq!(shorten(&theme, "laptop.local"), "laptop.local");
+        // Never strip everything
+        assert_eq!(shorten(&theme, ".corp"), ".corp");
+
+        let theme = strip(&[".*"]);
+        assert_eq!(shorten(&theme, "web1.example.com"), "web1");
+        assert_eq!(shorten(&theme, "web1"), "web1");
+    }
+
+    #[test]
+    fn palette() {
+        let mut theme = theme::DEFAULT;
+        theme.hostname_palette = vec![1, 2, 3, 4, 5];
+
+        // The same host always gets the same color, from the palette
+        let (_, bg, _) = host_style(&theme, "web1.example.com");
+        assert_eq!(host_style(&theme, "web1.example.com").1, bg);
+        assert_eq!(bg, theme.hostname_palette[hash("web1.example.com") as usize % 5]);
+        // FNV-1a, so colors don't change between versions
+        assert_eq!(hash(""), 0x811c_9dc5);
+        assert_eq!(hash("a"), 0xe40c_292c);
+
+        let colors: Vec<u8> = (0..20).map(|i| host_style(&theme, &format!("host{}", i)).1).collect();
+        assert!(colors.iter().all(|color| theme.hostname_palette.contains(color)));
+        assert!(colors.iter().any(|&color| color != colors[0]));
+    }
+
+    #[test]
+    fn entries() {
+        let mut theme = strip(&[".*"]);
+        theme.hostname_palette = vec![1, 2, 3];
+        let mut entry = HostEntry::new(String::from("prod"));
+        entry.name = Some(String::from("prod-*"));
+        entry.alias = Some(String::from("PROD"));
+        entry.bg = Some(124);
+        theme.hosts.push(entry);
+
+        assert_eq!(host_style(&theme, "prod-db.example.com"), (String::from("PROD"), 124, theme.hostname_fg));
+        assert_eq!(host_style(&theme, "dev.example.com").0, "dev");
+    }
+}
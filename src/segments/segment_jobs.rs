@@ -1,6 +1,12 @@
 use crate::{Powerline, Segment, Shell};
 
-pub fn segment_jobs(p: &mut Powerline) {
+pub fn segment_jobs(p: &mut Powerline, jobs: Option<u32>) {
+    if let Some(jobs) = jobs {
+        if jobs > 0 {
+            p.segments.push(Segment::new(p.theme.jobs_bg, p.theme.jobs_fg, jobs.to_string()));
+        }
+        return;
+    }
     p.segments.push(match p.shell {
         Shell::Bare => return,
         Shell::Bash =>
@@ -0,0 +1,309 @@
+use std::{env, ffi::OsString, fs, path::PathBuf};
+use crate::{glob, Powerline, Segment};
+
+/// What the prompt needs out of the kubeconfig files
+#[derive(Default)]
+struct KubeConfig {
+    current_context: Option<String>,
+    /// Context names and their namespaces
+    contexts: Vec<(String, Option<String>)>,
+}
+impl KubeConfig {
+    /// The current context and its namespace
+    fn current(&self) -> Option<(&str, &str)> {
+        let context = self.current_context.as_ref()?;
+        let namespace = self.contexts.iter()
+            .find(|(name, _)| name == context)
+            .and_then(|(_, namespace)| namespace.as_ref())
+            .map(String::as_str)
+            .unwrap_or("default");
+        Some((context, namespace))
+    }
+}
+
+/// The files listed in $KUBECONFIG, or the default one
+fn config_paths(kubeconfig: Option<OsString>) -> Vec<PathBuf> {
+    match kubeconfig {
+        Some(ref paths) if !paths.is_empty() => env::split_paths(paths)
+            .filter(|path| !path.as_os_str().is_empty())
+            .collect(),
+        _ => dirs::home_dir()
+            .map(|home| vec![home.join(".kube").join("config")])
+            .unwrap_or_default()
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    // Drop trailing comments, which need whitespace before the #
+    let value = match value.find(" #") {
+        Some(i) if !value.starts_with('"') && !value.starts_with('\'') => &value[..i],
+        _ => value
+    };
+    let value = value.trim();
+    for quote in &['"', '\''] {
+        if let Some(inner) = value.strip_prefix(*quote).and_then(|v| v.strip_suffix(*quote)) {
+            return inner;
+        }
+    }
+    value
+}
+
+/// A list item being read
+#[derive(Default)]
+struct Item {
+    /// The indentation of its '-', and of its keys
+    dash: usize,
+    keys: usize,
+    name: Option<String>,
+    namespace: Option<String>,
+    /// The indentation of its `context:` key, while inside that mapping
+    context: Option<usize>,
+    /// The indentation of the keys inside the `context:` mapping
+    context_keys: Option<usize>,
+}
+
+fn finish(item: &mut Option<Item>, config: &mut KubeConfig) {
+    if let Some(Item { name: Some(name), namespace, .. }) = item.take() {
+        // Like kubectl, the first file to define a context wins
+        if !config.contexts.iter().any(|(existing, _)| *existing == name) {
+            config.contexts.push((name, namespace));
+        }
+    }
+}
+
+fn key_value(line: &str) -> Option<(&str, &str)> {
+    let colon = line.find(':')?;
+    Some((line[..colon].trim(), unquote(&line[colon + 1..])))
+}
+
+/// Splits a flow mapping like `{cluster: c, namespace: ns}` into its keys
+/// and values. Values may be flow mappings themselves.
+fn flow_mapping(value: &str) -> Vec<(&str, &str)> {
+    let inner = match value.trim().strip_prefix('{').and_then(|value| value.strip_suffix('}')) {
+        Some(inner) => inner,
+        None => return Vec::new()
+    };
+    let mut entries = Vec::new();
+    let (mut depth, mut quote, mut start) = (0, None, 0);
+    for (i, c) in inner.char_indices() {
+        match (c, quote) {
+            ('"', None) | ('\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            (_, Some(_)) => (),
+            ('{', None) => depth += 1,
+            ('}', None) => depth -= 1,
+            (',', None) if depth == 0 => {
+                entries.extend(flow_entry(&inner[start..i]));
+                start = i + 1;
+            },
+            _ => ()
+        }
+    }
+    entries.extend(flow_entry(&inner[start..]));
+    entries
+}
+fn flow_entry(entry: &str) -> Option<(&str, &str)> {
+    let colon = entry.find(':')?;
+    let value = entry[colon + 1..].trim();
+    Some((unquote(&entry[..colon]), if value.starts_with('{') { value } else { unquote(value) }))
+}
+
+/// Picks the namespace out of a flow style `context:` value
+fn flow_namespace(value: &str) -> Option<String> {
+    flow_mapping(value).into_iter()
+        .find(|&(key, value)| key == "namespace" && !value.is_empty())
+        .map(|(_, value)| value.to_string())
+}
+
+/// Reads the parts of a kubeconfig we care about. This is not a YAML parser,
+/// it only understands the block style kubectl itself writes, and flow style
+/// mappings for whole contexts.
+fn parse(content: &str, config: &mut KubeConfig) {
+    let mut in_contexts = false;
+    let mut item: Option<Item> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+
+        if indent == 0 && !trimmed.starts_with('-') {
+            finish(&mut item, config);
+            in_contexts = false;
+            match key_value(trimmed) {
+                Some(("current-context", value)) if config.current_context.is_none() && !value.is_empty() => {
+                    config.current_context = Some(value.to_string());
+                },
+                Some(("contexts", _)) => in_contexts = true,
+                _ => ()
+            }
+            continue;
+        }
+        if !in_contexts {
+            continue;
+        }
+
+        let (indent, trimmed) = match trimmed.strip_prefix('-') {
+            Some(rest) if item.as_ref().map(|item| indent <= item.dash).unwrap_or(true) => {
+                finish(&mut item, config);
+                let rest_trimmed = rest.trim_start();
+                let keys = indent + 1 + rest.len() - rest_trimmed.len();
+
+                // The whole item on one line, like `- {name: a, context: {...}}`
+                if rest_trimmed.starts_with('{') {
+                    let mut flow = Item::default();
+                    for (key, value) in flow_mapping(rest_trimmed) {
+                        match key {
+                            "name" => flow.name = Some(value.to_string()),
+                            "context" => flow.namespace = flow_namespace(value),
+                            _ => ()
+                        }
+                    }
+                    finish(&mut Some(flow), config);
+                    continue;
+                }
+                item = Some(Item { dash: indent, keys, ..Item::default() });
+                (keys, rest_trimmed)
+            },
+            _ => (indent, trimmed)
+        };
+        let item = match item {
+            Some(ref mut item) => item,
+            None => continue
+        };
+
+        // Leaving the `context:` mapping
+        if item.context.map(|context| indent <= context).unwrap_or(false) {
+            item.context = None;
+            item.context_keys = None;
+        }
+        if let Some(context) = item.context {
+            // Only the mapping's own keys, not ones nested deeper
+            let keys = *item.context_keys.get_or_insert(indent);
+            match key_value(trimmed) {
+                Some(("namespace", value)) if indent == keys && indent > context && !value.is_empty() => {
+                    item.namespace = Some(value.to_string());
+                },
+                _ => ()
+            }
+            continue;
+        }
+        if indent != item.keys {
+            continue;
+        }
+        match key_value(trimmed) {
+            Some(("name", value)) => item.name = Some(value.to_string()),
+            Some(("context", "")) => item.context = Some(indent),
+            Some(("context", value)) => item.namespace = flow_namespace(value),
+            _ => ()
+        }
+    }
+    finish(&mut item, config);
+}
+
+pub fn segment_kube(p: &mut Powerline) {
+    let mut config = KubeConfig::default();
+    for path in config_paths(env::var_os("KUBECONFIG")) {
+        if let Ok(content) = fs::read_to_string(&path) {
+            parse(&content, &mut config);
+        }
+    }
+    let (context, namespace) = match config.current() {
+        Some(current) => current,
+        None => return
+    };
+
+    let entry = p.theme.kube.iter().find(|entry| glob::matches(entry.context(), context));
+    let bg = entry.and_then(|entry| entry.bg).unwrap_or(p.theme.kube_bg);
+    let fg = entry.and_then(|entry| entry.fg).unwrap_or(p.theme.kube_fg);
+    let name = entry.and_then(|entry| entry.alias.as_ref()).map(String::as_str).unwrap_or(context);
+
+    p.segments.push(Segment::new(bg, fg, format!("{}/{}", name, namespace)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn current(files: &[&str]) -> Option<(String, String)> {
+        let mut config = KubeConfig::default();
+        for content in files {
+            parse(content, &mut config);
+        }
+        config.current().map(|(context, namespace)| (context.to_string(), namespace.to_string()))
+    }
+
+    fn pair(context: &str, namespace: &str) -> Option<(String, String)> {
+        Some((context.to_string(), namespace.to_string()))
+    }
+
+    #[test]
+    fn block_style() {
+        let config = "\
+apiVersion: v1
+contexts:
+- context:
+    cluster: dev
+    namespace: web
+    user: dev
+  name: dev
+- context:
+    cluster: prod
+    extensions:
+    - name: other
+      extension:
+        namespace: wrong
+    user: prod
+  name: prod
+current-context: prod
+";
+        assert_eq!(current(&[config]), pair("prod", "default"));
+        assert_eq!(current(&[&config.replace("current-context: prod", "current-context: dev")]), pair("dev", "web"));
+    }
+
+    #[test]
+    fn flow_style() {
+        let config = "\
+contexts:
+- name: a
+  context: {cluster: a, namespace: \"ns-a\", user: a}
+- {name: 'b', context: {cluster: b, namespace: ns-b}}
+current-context: b
+";
+        assert_eq!(current(&[config]), pair("b", "ns-b"));
+        assert_eq!(current(&[&config.replace("current-context: b", "current-context: a")]), pair("a", "ns-a"));
+    }
+
+    #[test]
+    fn quoted() {
+        let config = "\
+current-context: \"gke_project_zone_name\" # set by gcloud
+contexts:
+  - name: \"gke_project_zone_name\"
+    context:
+      cluster: gke
+      namespace: 'kube-system'
+";
+        assert_eq!(current(&[config]), pair("gke_project_zone_name", "kube-system"));
+    }
+
+    #[test]
+    fn missing() {
+        assert_eq!(current(&["contexts:\n- context:\n    cluster: a\n  name: a\ncurrent-context: a\n"]), pair("a", "default"));
+        assert_eq!(current(&["current-context: \"\"\n"]), None);
+    }
+
+    #[test]
+    fn several_files() {
+        let paths = config_paths(Some(OsString::from("/a/config:/b/config")));
+        assert_eq!(paths, vec![PathBuf::from("/a/config"), PathBuf::from("/b/config")]);
+
+        // The first current-context wins, and contexts can come from any file
+        let first = "current-context: b\ncontexts:\n- context:\n    namespace: first\n  name: a\n";
+        let second = "current-context: a\ncontexts:\n- context:\n    namespace: second\n  name: a\n- context:\n    namespace: ns-b\n  name: b\n";
+        assert_eq!(current(&[first, second]), pair("b", "ns-b"));
+        assert_eq!(current(&[&first.replace("current-context: b", "current-context: a"), second]), pair("a", "first"));
+    }
+}
@@ -0,0 +1,62 @@
+use std::{fs, path::Path, thread};
+use crate::{Powerline, Segment};
+
+/// The CPUs this process may run on, which takes CPU affinity and cgroup
+/// quotas into account unlike counting them in /proc/cpuinfo
+fn cpus() -> u32 {
+    thread::available_parallelism().map(|cpus| cpus.get() as u32).unwrap_or(1)
+}
+
+/// The 1 minute load average from /proc/loadavg
+fn load(loadavg: &str) -> Option<f64> {
+    loadavg.split_whitespace().next()?.parse().ok()
+}
+
+/// The load as a percentage of the CPU count
+fn percent(load: f64, cpus: u32) -> u32 {
+    (load * 100.0 / f64::from(cpus.max(1))).round() as u32
+}
+
+/// Shows the load average, colored by how busy the CPUs are. `threshold` is
+/// a percentage of the CPU count, below which nothing is shown.
+pub fn segment_load(p: &mut Powerline, proc: &Path, threshold: u8) {
+    let load = match fs::read_to_string(proc.join("loadavg")).ok().and_then(|loadavg| load(&loadavg)) {
+        Some(load) => load,
+        None => return
+    };
+    let percent = percent(load, cpus());
+    if percent < u32::from(threshold) {
+        return;
+    }
+
+    let t = &p.theme;
+    let (bg, fg) = if percent >= u32::from(t.load_critical_level) {
+        (t.load_critical_bg, t.load_critical_fg)
+    } else if percent >= u32::from(t.load_warning_level) {
+        (t.load_warning_bg, t.load_warning_fg)
+    } else {
+        (t.load_bg, t.load_fg)
+    };
+    p.segments.push(Segment::new(bg, fg, format!("load {:.2}", load)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loadavg() {
+        assert_eq!(load("0.40 0.30 0.20 1/123 4567\n"), Some(0.4));
+        assert_eq!(load("12.05 8.00 4.00 5/1234 4567\n"), Some(12.05));
+        assert_eq!(load(""), None);
+    }
+
+    #[test]
+    fn relative_to_cpus() {
+        assert_eq!(percent(0.4, 4), 10);
+        assert_eq!(percent(2.8, 4), 70);
+        assert_eq!(percent(2.5, 2), 125);
+        assert_eq!(percent(1.0, 0), 100);
+        assert!(cpus() >= 1);
+    }
+}
@@ -0,0 +1,63 @@
+use std::{fs, path::Path};
+use crate::{Powerline, Segment};
+
+/// The percentage of memory in use according to /proc/meminfo, counting
+/// caches the kernel can drop as free
+fn used_percent(meminfo: &str) -> Option<u32> {
+    let field = |name: &str| -> Option<u64> {
+        meminfo.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))?
+            .split_whitespace().next()?
+            .parse().ok()
+    };
+    let total = field("MemTotal").filter(|&total| total > 0)?;
+    // Kernels before 3.14 don't have MemAvailable
+    let available = field("MemAvailable").or_else(|| Some(field("MemFree")? + field("Buffers")? + field("Cached")?))?;
+    Some((100 - available.min(total) * 100 / total) as u32)
+}
+
+/// Shows how much memory is in use. Nothing is shown below `threshold`
+/// percent.
+pub fn segment_mem(p: &mut Powerline, proc: &Path, threshold: u8) {
+    let used = match fs::read_to_string(proc.join("meminfo")).ok().and_then(|meminfo| used_percent(&meminfo)) {
+        Some(used) => used,
+        None => return
+    };
+    if used < u32::from(threshold) {
+        return;
+    }
+
+    let t = &p.theme;
+    let (bg, fg) = if used >= u32::from(t.mem_critical_level) {
+        (t.mem_critical_bg, t.mem_critical_fg)
+    } else if used >= u32::from(t.mem_warning_level) {
+        (t.mem_warning_bg, t.mem_warning_fg)
+    } else {
+        (t.mem_bg, t.mem_fg)
+    };
+    p.segments.push(Segment::new(bg, fg, format!("mem {}%", used)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn available() {
+        let meminfo = "MemTotal:       16000000 kB\nMemFree:         1000000 kB\nMemAvailable:    2400000 kB\n";
+        assert_eq!(used_percent(meminfo), Some(85));
+    }
+
+    #[test]
+    fn old_kernel() {
+        let meminfo = "MemTotal:       1000 kB\nMemFree:         100 kB\nBuffers:         100 kB\nCached:          300 kB\n";
+        assert_eq!(used_percent(meminfo), Some(50));
+    }
+
+    #[test]
+    fn broken() {
+        assert_eq!(used_percent("MemTotal:       0 kB\nMemAvailable:    0 kB\n"), None);
+        assert_eq!(used_percent("MemTotal:       1000 kB\n"), None);
+        assert_eq!(used_percent(""), None);
+    }
+}
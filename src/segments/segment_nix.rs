@@ -2,7 +2,6 @@ use std::env;
 use crate::{Powerline, Segment};
 
 pub fn segment_nix(p: &mut Powerline) {
-    // TODO: Generalize this to any environment variable?
     if let Ok(val) = env::var("IN_NIX_SHELL") {
         p.segments.push(Segment::new(
             p.theme.nixshell_bg,
@@ -0,0 +1,103 @@
+//! Segments from external programs. Each plugin is run with a JSON request
+//! on stdin:
+//!
+//! ```json
+//! {"version": 1, "cwd": "/home/user", "shell": "bash", "exit_code": 0,
+//!  "terminal_width": 80, "theme": "default"}
+//! ```
+//!
+//! and answers on stdout with the version it speaks and any number of
+//! segments:
+//!
+//! ```json
+//! {"version": 1, "segments": [
+//!     {"text": "hello", "bg": 24, "fg": 231, "bold": false,
+//!      "priority": 0, "conditional": false}
+//! ]}
+//! ```
+//!
+//! Everything but "text" is optional. Segments from all plugins are ordered
+//! by priority, highest first. Plugins that fail, time out or answer with a
+//! different version are skipped.
+
+use serde_json::{json, Value};
+use std::{env, mem, path::Path, time::Duration};
+use crate::{command, Powerline, Segment, Shell};
+
+pub const PROTOCOL_VERSION: u64 = 1;
+
+fn terminal_width() -> Option<u16> {
+    if let Some(columns) = env::var("COLUMNS").ok().and_then(|columns| columns.parse().ok()) {
+        return Some(columns);
+    }
+    // stdout is most likely captured by the shell, but stderr isn't
+    let mut size: libc::winsize = unsafe { mem::zeroed() };
+    if unsafe { libc::ioctl(libc::STDERR_FILENO, libc::TIOCGWINSZ, &mut size) } == 0 && size.ws_col > 0 {
+        Some(size.ws_col)
+    } else {
+        None
+    }
+}
+
+fn parse_segment(p: &Powerline, segment: &Value) -> Option<(i64, Segment)> {
+    let color = |key: &str, default: u8| match segment.get(key).and_then(Value::as_u64) {
+        Some(color) if color <= 255 => color as u8,
+        _ => default
+    };
+    let flag = |key: &str| segment.get(key).and_then(Value::as_bool).unwrap_or(false);
+
+    let text = segment.get("text")?.as_str()?.to_string();
+    let priority = segment.get("priority").and_then(Value::as_i64).unwrap_or(0);
+
+    let mut segment = Segment::new(color("bg", p.theme.plugin_bg), color("fg", p.theme.plugin_fg), text);
+    if flag("bold") {
+        segment = segment.bold();
+    }
+    if flag("conditional") {
+        segment = segment.into_conditional();
+    }
+    Some((priority, segment))
+}
+
+pub fn segment_plugin(p: &mut Powerline, error: u8, theme: Option<&str>) {
+    let request = json!({
+        "version": PROTOCOL_VERSION,
+        "cwd": env::current_dir().ok().map(|cwd| cwd.to_string_lossy().into_owned()),
+        "shell": match p.shell {
+            Shell::Bare => "bare",
+            Shell::Bash => "bash",
+            Shell::Zsh  => "zsh"
+        },
+        "exit_code": error,
+        "terminal_width": terminal_width(),
+        "theme": theme
+            .and_then(|theme| Path::new(theme).file_stem())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| String::from("default"))
+    }).to_string();
+
+    let running: Vec<_> = p.theme.plugins.iter()
+        .filter_map(|plugin| {
+            let timeout = plugin.timeout.map(Duration::from_millis).unwrap_or(command::DEFAULT_TIMEOUT);
+            command::spawn(plugin.command.as_ref()?, Some(request.clone()), timeout)
+        })
+        .collect();
+
+    let mut segments = Vec::new();
+    for pending in running {
+        let response: Value = match pending.wait().and_then(|output| serde_json::from_str(&output).ok()) {
+            Some(response) => response,
+            None => continue
+        };
+        if response.get("version").and_then(Value::as_u64) != Some(PROTOCOL_VERSION) {
+            continue;
+        }
+        if let Some(list) = response.get("segments").and_then(Value::as_array) {
+            segments.extend(list.iter().filter_map(|segment| parse_segment(p, segment)));
+        }
+    }
+
+    // Stable, so equal priorities keep their order
+    segments.sort_by(|(a, _), (b, _)| b.cmp(a));
+    p.segments.extend(segments.into_iter().map(|(_, segment)| segment));
+}
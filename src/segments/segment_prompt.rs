@@ -0,0 +1,19 @@
+use crate::{format, Powerline, Segment};
+
+pub fn segment_prompt(p: &mut Powerline, pipestatus: &[u8]) {
+    let failed = pipestatus.iter().any(|&code| code != 0);
+    let (bg, fg, user, root) = if failed {
+        (p.theme.cmd_failed_bg, p.theme.cmd_failed_fg, p.theme.prompt_user_failed_char, p.theme.prompt_root_failed_char)
+    } else {
+        (p.theme.cmd_passed_bg, p.theme.cmd_passed_fg, p.theme.prompt_user_char, p.theme.prompt_root_char)
+    };
+
+    if let Some(escape) = format::root(p.shell, user, root) {
+        p.segments.push(Segment::new(bg, fg, escape).dont_escape());
+        return;
+    }
+
+    let is_root = unsafe { libc::geteuid() } == 0;
+
+    p.segments.push(Segment::new(bg, fg, if is_root { root } else { user }.to_string()));
+}
@@ -12,15 +12,14 @@ extern "C" {
     fn getpid() -> c_int; // std::process::id() is unstable
 }
 
-pub fn segment_ps(p: &mut Powerline) {
+pub fn segment_ps(p: &mut Powerline, proc: &Path) {
     let pid = unsafe { getpid() };
     let tty = {
-        let mut path = String::with_capacity(6 + 4 + 5); // 4 = reserved pid length
-        path.push_str("/proc/");
+        let mut path = String::with_capacity(4 + 5); // 4 = reserved pid length
         write!(path, "{}", pid).unwrap();
         path.push_str("/stat");
 
-        match get_process_tty(&Path::new(&path)) {
+        match get_process_tty(&proc.join(path)) {
             Some(tty) => tty,
             None => return
         }
@@ -28,7 +27,7 @@ pub fn segment_ps(p: &mut Powerline) {
 
     let mut count = -1isize;
 
-    if let Ok(list) = fs::read_dir("/proc/") {
+    if let Ok(list) = fs::read_dir(proc) {
         for entry in list {
             let entry = match entry {
                 Ok(entry) => entry,
@@ -40,7 +39,7 @@ pub fn segment_ps(p: &mut Powerline) {
                     .and_then(|name| name
                         .to_str()
                         .map(|s| {
-                            s.chars().all(|c| c >= '0' && c <= '9')
+                            s.chars().all(|c| c.is_ascii_digit())
                                 && s.parse() != Ok(pid)
                         }))
                     .unwrap_or(false) {
@@ -60,7 +59,7 @@ pub fn segment_ps(p: &mut Powerline) {
     }
 }
 pub fn get_process_tty(file: &Path) -> Option<usize> {
-    fs::read_to_string(&file).ok()?
+    fs::read_to_string(file).ok()?
         .split_whitespace().nth(PROC_STAT_PID)
         .and_then(|n| n.parse().ok())
 }
@@ -1,9 +1,60 @@
 use crate::{Powerline, Segment};
 
-pub fn segment_root(p: &mut Powerline, error: u8) {
-    if error == 0 {
+/// Gives exit codes a meaningful name where possible: 126 and 127 are what
+/// shells use for commands that can't be run, and anything above 128 means
+/// the command was killed by signal code - 128.
+fn code_name(code: u8) -> Option<&'static str> {
+    match code {
+        126 => return Some("NOEXEC"),
+        127 => return Some("NOTFOUND"),
+        129..=255 => (),
+        _ => return None
+    }
+    Some(match (code - 128) as libc::c_int {
+        libc::SIGHUP  => "HUP",
+        libc::SIGINT  => "INT",
+        libc::SIGQUIT => "QUIT",
+        libc::SIGILL  => "ILL",
+        libc::SIGTRAP => "TRAP",
+        libc::SIGABRT => "ABRT",
+        libc::SIGBUS  => "BUS",
+        libc::SIGFPE  => "FPE",
+        libc::SIGKILL => "KILL",
+        libc::SIGUSR1 => "USR1",
+        libc::SIGSEGV => "SEGV",
+        libc::SIGUSR2 => "USR2",
+        libc::SIGPIPE => "PIPE",
+        libc::SIGALRM => "ALRM",
+        libc::SIGTERM => "TERM",
+        libc::SIGCHLD => "CHLD",
+        libc::SIGCONT => "CONT",
+        libc::SIGSTOP => "STOP",
+        libc::SIGTSTP => "TSTP",
+        libc::SIGTTIN => "TTIN",
+        libc::SIGTTOU => "TTOU",
+        libc::SIGXCPU => "XCPU",
+        libc::SIGXFSZ => "XFSZ",
+        libc::SIGWINCH => "WINCH",
+        _ => return None
+    })
+}
+
+fn format_code(code: u8, verbose: bool) -> String {
+    match code_name(code) {
+        Some(name) if verbose => format!("{} {}", code, name),
+        Some(name) => name.to_string(),
+        None => code.to_string()
+    }
+}
+
+pub fn segment_root(p: &mut Powerline, pipestatus: &[u8], verbose: bool) {
+    if pipestatus.iter().all(|&code| code == 0) {
         p.segments.push(Segment::new(p.theme.cmd_passed_bg, p.theme.cmd_passed_fg, "𒁍 "));
         return;
     }
-    p.segments.push(Segment::new(p.theme.cmd_failed_bg, p.theme.cmd_failed_fg, error.to_string()));
+    let text = pipestatus.iter()
+        .map(|&code| format_code(code, verbose))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    p.segments.push(Segment::new(p.theme.cmd_failed_bg, p.theme.cmd_failed_fg, text));
 }
@@ -1,8 +1,104 @@
-use std::env;
+use std::{env, fs, path::Path};
 use crate::{Powerline, Segment};
 
-pub fn segment_ssh(p: &mut Powerline) {
-    if env::var("SSH_CLIENT").is_ok() {
-        p.segments.push(Segment::new(p.theme.ssh_bg, p.theme.ssh_fg, p.theme.ssh_char.to_string()));
+/// Reads the command name and parent of a process from its /proc/<pid>/stat
+fn parse_stat(stat: &str) -> Option<(String, libc::pid_t)> {
+    // The name is in parentheses and can contain both spaces and parentheses
+    let (start, end) = (stat.find('(')?, stat.rfind(')')?);
+    let name = stat.get(start + 1..end)?.to_string();
+    let ppid = stat[end + 1..].split_whitespace().nth(1)?.parse().ok()?;
+    Some((name, ppid))
+}
+
+/// Whether a process called `name` is among the ancestors of `pid`,
+/// looking each one up with `stat`
+fn has_ancestor(mut pid: libc::pid_t, name: &str, stat: impl Fn(libc::pid_t) -> Option<(String, libc::pid_t)>) -> bool {
+    // Just in case something loops
+    for _ in 0..64 {
+        match stat(pid) {
+            Some((ref process, _)) if process == name => return true,
+            Some((_, ppid)) if ppid > 1 => pid = ppid,
+            _ => break
+        }
+    }
+    false
+}
+
+/// Mosh doesn't set any variables, but the shell is started by mosh-server
+fn is_mosh(proc: &Path) -> bool {
+    has_ancestor(unsafe { libc::getppid() }, "mosh-server", |pid| {
+        parse_stat(&fs::read_to_string(proc.join(pid.to_string()).join("stat")).ok()?)
+    })
+}
+
+fn env_nonempty(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Whether this is a remote session, the same way the ssh module decides
+pub fn in_ssh_session(proc: &Path) -> bool {
+    ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"].iter().any(|name| env_nonempty(name).is_some()) || is_mosh(proc)
+}
+
+pub fn segment_ssh(p: &mut Powerline, proc: &Path, client_ip: bool) {
+    // SSH_CLIENT is the old name, and SSH_TTY is all that's left after some
+    // ways of switching users
+    let connection = env_nonempty("SSH_CONNECTION").or_else(|| env_nonempty("SSH_CLIENT"));
+    let mosh = is_mosh(proc);
+    if connection.is_none() && env_nonempty("SSH_TTY").is_none() && !mosh {
+        return;
+    }
+
+    let mut text = p.theme.ssh_char.to_string();
+
+    // Counted by the init scripts. LC_* variables are forwarded by many ssh
+    // configurations, which is what lets this count hops.
+    let depth = env_nonempty("LC_POWERLINE_SSH_DEPTH")
+        .and_then(|depth| depth.parse::<u32>().ok())
+        .unwrap_or(1);
+    if depth > 1 {
+        text.push(' ');
+        text.push_str(&depth.to_string());
+    }
+    if mosh {
+        text.push_str(" mosh");
+    }
+    if client_ip {
+        if let Some(ip) = connection.as_ref().and_then(|connection| connection.split_whitespace().next()) {
+            text.push(' ');
+            text.push_str(ip);
+        }
+    }
+
+    p.segments.push(Segment::new(p.theme.ssh_bg, p.theme.ssh_fg, text));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stat() {
+        assert_eq!(parse_stat("1234 (bash) S 1200 1234 1234 34816 1234 4194304"), Some((String::from("bash"), 1200)));
+        assert_eq!(parse_stat("99 (weird) name)) R 42 99 99 0 -1"), Some((String::from("weird) name)"), 42)));
+        assert_eq!(parse_stat("99 bash S 42"), None);
+    }
+
+    #[test]
+    fn mosh_parent() {
+        let processes = [
+            (1, "1 (systemd) S 0"),
+            (200, "200 (mosh-server) S 1"),
+            (210, "210 (zsh) S 200"),
+            (220, "220 (tmux: client) S 210"),
+            (230, "230 (bash) S 1")
+        ];
+        let stat = |pid| processes.iter()
+            .find(|&&(candidate, _)| candidate == pid)
+            .and_then(|(_, stat)| parse_stat(stat));
+        assert!(has_ancestor(220, "mosh-server", stat));
+        assert!(has_ancestor(200, "mosh-server", stat));
+        assert!(!has_ancestor(230, "mosh-server", stat));
+        assert!(!has_ancestor(404, "mosh-server", stat));
     }
 }
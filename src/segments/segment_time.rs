@@ -4,7 +4,7 @@ use crate::{Powerline, Segment, Shell};
 
 pub fn segment_time(p: &mut Powerline, strftime: &str) {
     let (bg, fg) = (p.theme.time_bg, p.theme.time_fg);
-    if p.shell == Shell::Bare || strftime != crate::cli::TIME_FORMAT_DEFAULT {
+    if p.shell == Shell::Bare || strftime != crate::module::TIME_FORMAT_DEFAULT {
         #[cfg(feature = "chrono")]
         {
             let now = Local::now();
@@ -0,0 +1,267 @@
+use std::{
+    env,
+    fs,
+    path::{Path, PathBuf},
+    time::{Instant, UNIX_EPOCH}
+};
+use crate::{command, segments, Powerline, Segment};
+
+/// How many active versions to remember
+const CACHE_LINES: usize = 64;
+
+struct Toolchain {
+    name: &'static str,
+    /// Files that mark a project using this toolchain
+    markers: &'static [&'static str],
+    /// Reads the version the project in a directory pins
+    pinned: fn(&Path) -> Option<String>,
+    /// Prints the version of the toolchain in $PATH
+    command: &'static str,
+    version: fn(&str) -> Option<&str>
+}
+
+fn read(dir: &Path, name: &str) -> Option<String> {
+    fs::read_to_string(dir.join(name)).ok()
+}
+/// The first line that isn't empty or a comment, like in .nvmrc
+fn first_line(content: &str) -> Option<String> {
+    content.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+}
+fn toml_value(content: &str, section: &str, key: &str) -> Option<String> {
+    let value = segments::ini_value(content, section, key)?;
+    Some(value.trim_matches(|c| c == '"' || c == '\'').to_string())
+}
+
+/// The legacy rust-toolchain file is either TOML or just the channel
+fn rust_toolchain(content: &str) -> Option<String> {
+    if content.contains("[toolchain]") {
+        toml_value(content, "toolchain", "channel")
+    } else {
+        first_line(content)
+    }
+}
+#[cfg(feature = "serde_json")]
+fn package_node(content: &str) -> Option<String> {
+    let package: serde_json::Value = serde_json::from_str(content).ok()?;
+    package["engines"]["node"].as_str().map(String::from)
+}
+fn pyproject_python(content: &str) -> Option<String> {
+    toml_value(content, "project", "requires-python")
+        .or_else(|| toml_value(content, "tool.poetry.dependencies", "python"))
+}
+/// The toolchain directive of a go.mod, falling back to the go directive
+fn gomod_go(content: &str) -> Option<String> {
+    let directive = |name: &str| content.lines()
+        .filter_map(|line| line.trim().strip_prefix(name))
+        .find(|rest| rest.starts_with(char::is_whitespace))
+        .map(|rest| rest.trim().to_string());
+    directive("toolchain")
+        .map(|toolchain| toolchain.trim_start_matches("go").to_string())
+        .or_else(|| directive("go"))
+}
+
+// Pins are only read from the project directory itself, so one in some
+// outer directory can't override the project's own
+
+fn rust_pinned(dir: &Path) -> Option<String> {
+    read(dir, "rust-toolchain.toml").and_then(|content| toml_value(&content, "toolchain", "channel"))
+        .or_else(|| rust_toolchain(&read(dir, "rust-toolchain")?))
+        .or_else(|| toml_value(&read(dir, "Cargo.toml")?, "package", "rust-version"))
+}
+fn node_pinned(dir: &Path) -> Option<String> {
+    let pinned = read(dir, ".nvmrc").and_then(|content| first_line(&content))
+        .or_else(|| first_line(&read(dir, ".node-version")?));
+    #[cfg(feature = "serde_json")]
+    let pinned = pinned.or_else(|| package_node(&read(dir, "package.json")?));
+    pinned
+}
+fn python_pinned(dir: &Path) -> Option<String> {
+    read(dir, ".python-version").and_then(|content| first_line(&content))
+        .or_else(|| pyproject_python(&read(dir, "pyproject.toml")?))
+}
+fn go_pinned(dir: &Path) -> Option<String> {
+    gomod_go(&read(dir, "go.mod")?)
+}
+
+/// "rustc 1.70.0 (90c541806 2023-05-31)" and "Python 3.11.4"
+fn second_word(output: &str) -> Option<&str> {
+    output.split_whitespace().nth(1)
+}
+
+const RUST: Toolchain = Toolchain {
+    name: "rust",
+    markers: &["rust-toolchain.toml", "rust-toolchain", "Cargo.toml"],
+    pinned: rust_pinned,
+    command: "rustc --version",
+    version: second_word
+};
+const NODE: Toolchain = Toolchain {
+    name: "node",
+    markers: &["package.json", ".nvmrc", ".node-version"],
+    pinned: node_pinned,
+    command: "node --version",
+    version: |output| Some(output.trim().trim_start_matches('v'))
+};
+const PYTHON: Toolchain = Toolchain {
+    name: "python",
+    markers: &["pyproject.toml", ".python-version"],
+    pinned: python_pinned,
+    command: "python3 --version",
+    version: second_word
+};
+const GO: Toolchain = Toolchain {
+    name: "go",
+    markers: &["go.mod"],
+    pinned: go_pinned,
+    command: "go version",
+    version: |output| output.split_whitespace().nth(2).map(|version| version.trim_start_matches("go"))
+};
+
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|path| path.is_file())
+}
+
+fn cache_path() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join("powerline-rs").join("toolchains"))
+}
+
+/// Gets the version of the toolchain in $PATH. Running it takes a while, so
+/// the result is cached until the executable, the project or its pinned
+/// version changes. Version managers like rustup and pyenv pick the version
+/// based on the project, which is why that's part of the key. Failures are
+/// cached too, so a broken toolchain doesn't slow down every prompt, but
+/// timeouts aren't, since those may just be a busy machine.
+fn active_version(toolchain: &Toolchain, project: &Path, pinned: Option<&str>) -> Option<String> {
+    let program = toolchain.command.split_whitespace().next()?;
+    let exe = find_in_path(program)?;
+    let mtime = fs::metadata(&exe).ok()?
+        .modified().ok()?
+        .duration_since(UNIX_EPOCH).ok()?
+        .as_secs();
+    let key = format!("{}\t{}\t{}\t{}\t{}", toolchain.name, exe.display(), mtime, project.display(), pinned.unwrap_or(""));
+
+    let cache = cache_path();
+    let cached = cache.as_ref()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default();
+    for line in cached.lines() {
+        if let Some(version) = line.strip_prefix(&key).and_then(|rest| rest.strip_prefix('\t')) {
+            return Some(version.to_string()).filter(|version| !version.is_empty());
+        }
+    }
+
+    let started = Instant::now();
+    let output = command::spawn(toolchain.command, None, command::DEFAULT_TIMEOUT)
+        .and_then(|pending| pending.wait());
+    if output.is_none() && started.elapsed() >= command::DEFAULT_TIMEOUT {
+        return None;
+    }
+    let version = output
+        .and_then(|output| (toolchain.version)(&output).map(String::from))
+        .unwrap_or_default();
+
+    if let Some(path) = cache {
+        let mut lines: Vec<&str> = cached.lines().collect();
+        let skip = (lines.len() + 1).saturating_sub(CACHE_LINES);
+        lines.drain(..skip);
+        let line = format!("{}\t{}", key, version);
+        lines.push(&line);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).ok();
+        }
+        fs::write(path, lines.join("\n") + "\n").ok();
+    }
+
+    Some(version).filter(|version| !version.is_empty())
+}
+
+fn segment_toolchain(p: &mut Powerline, toolchain: &Toolchain, bg: u8, fg: u8, active: bool) {
+    let cwd = match env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(_) => return
+    };
+    let project = match cwd.ancestors().find(|dir| toolchain.markers.iter().any(|marker| dir.join(marker).exists())) {
+        Some(project) => project,
+        None => return
+    };
+
+    let pinned = (toolchain.pinned)(project);
+    let active = if active { active_version(toolchain, project, pinned.as_deref()) } else { None };
+
+    let text = match (pinned, active) {
+        (Some(pinned), Some(active)) if !active.starts_with(&pinned) => format!("{} {} ({})", toolchain.name, pinned, active),
+        (_, Some(version)) | (Some(version), None) => format!("{} {}", toolchain.name, version),
+        (None, None) => toolchain.name.to_string()
+    };
+    p.segments.push(Segment::new(bg, fg, text));
+}
+
+pub fn segment_rust(p: &mut Powerline, active: bool) {
+    let (bg, fg) = (p.theme.rust_bg, p.theme.rust_fg);
+    segment_toolchain(p, &RUST, bg, fg, active);
+}
+pub fn segment_node(p: &mut Powerline, active: bool) {
+    let (bg, fg) = (p.theme.node_bg, p.theme.node_fg);
+    segment_toolchain(p, &NODE, bg, fg, active);
+}
+pub fn segment_python(p: &mut Powerline, active: bool) {
+    let (bg, fg) = (p.theme.python_bg, p.theme.python_fg);
+    segment_toolchain(p, &PYTHON, bg, fg, active);
+}
+pub fn segment_go(p: &mut Powerline, active: bool) {
+    let (bg, fg) = (p.theme.go_bg, p.theme.go_fg);
+    segment_toolchain(p, &GO, bg, fg, active);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn first_lines() {
+        assert_eq!(first_line("# lts\n\n  18.17.0  \n20\n"), Some(String::from("18.17.0")));
+        assert_eq!(first_line("\n# nothing\n"), None);
+    }
+
+    #[test]
+    fn rust() {
+        assert_eq!(rust_toolchain("nightly-2023-06-01\n"), Some(String::from("nightly-2023-06-01")));
+        assert_eq!(rust_toolchain("[toolchain]\nchannel = \"1.70.0\"\ncomponents = [\"clippy\"]\n"), Some(String::from("1.70.0")));
+        assert_eq!(rust_toolchain("[toolchain]\ncomponents = [\"clippy\"]\n"), None);
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn node() {
+        assert_eq!(package_node(r#"{"name": "x", "engines": {"node": ">=18"}}"#), Some(String::from(">=18")));
+        assert_eq!(package_node(r#"{"name": "x"}"#), None);
+    }
+
+    #[test]
+    fn python() {
+        assert_eq!(pyproject_python("[project]\nname = \"x\"\nrequires-python = \">=3.9\"\n"), Some(String::from(">=3.9")));
+        assert_eq!(pyproject_python("[tool.poetry.dependencies]\npython = '^3.11'\n"), Some(String::from("^3.11")));
+        assert_eq!(pyproject_python("[tool.black]\nline-length = 100\n"), None);
+    }
+
+    #[test]
+    fn go() {
+        assert_eq!(gomod_go("module example.com/x\n\ngo 1.21\n\ntoolchain go1.21.3\n"), Some(String::from("1.21.3")));
+        assert_eq!(gomod_go("module example.com/x\n\ngo 1.20\n"), Some(String::from("1.20")));
+        assert_eq!(gomod_go("module example.com/gopher\n"), None);
+    }
+
+    #[test]
+    fn pinned_in_project_only() {
+        let root = TempDir::new("toolchain");
+        root.write(".nvmrc", "16\n");
+        let package = root.write("project/package.json", "{}");
+        assert_eq!(node_pinned(package.parent().unwrap()), None);
+    }
+}
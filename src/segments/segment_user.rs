@@ -1,40 +1,123 @@
-use std::borrow::Cow;
-use crate::{Powerline, Segment, Shell};
+use std::{borrow::Cow, fs, path::Path};
+use crate::{segments, Powerline, Segment, Shell};
 
-pub fn segment_user(p: &mut Powerline) {
-    let (bg, fg) = (p.theme.username_bg, p.theme.username_fg);
-    #[cfg(feature = "users")]
-    let mut bg = bg;
-    let mut fg = fg;
+/// When the user and host modules are shown, and how
+#[derive(Clone, Default)]
+pub struct UserHostOptions {
+    /// Hide them for this user, unless over ssh, after switching users or
+    /// as root. Like agnoster's DEFAULT_USER.
+    pub default_user: Option<String>,
+    /// Only show them over ssh, or after switching users (either one is
+    /// enough if both are set). They're always shown for root.
+    pub only_ssh: bool,
+    pub only_switched: bool,
+    /// Show one user@host tile from the user module instead of two
+    pub combined: bool,
+}
+impl UserHostOptions {
+    /// Whether to show them, reading procfs from `proc`
+    pub fn visible(&self, proc: &Path) -> bool {
+        if self.default_user.is_none() && !self.only_ssh && !self.only_switched {
+            return true;
+        }
+        let uid = unsafe { libc::getuid() };
+        if uid == 0 {
+            return true;
+        }
+        let ssh = segments::in_ssh_session(proc);
+        let switched = switched_user(proc, uid);
 
+        let usual = self.default_user.as_ref()
+            .map(|default| username().as_ref() == Some(default))
+            .unwrap_or(false);
+        if usual && !ssh && !switched {
+            return false;
+        }
+        if self.only_ssh || self.only_switched {
+            return (self.only_ssh && ssh) || (self.only_switched && switched);
+        }
+        true
+    }
+}
+
+/// Whether the user isn't the one who logged in, like after su or sudo
+fn switched_user(proc: &Path, uid: libc::uid_t) -> bool {
+    let login = fs::read_to_string(proc.join("self").join("loginuid")).ok()
+        .and_then(|login| login.trim().parse::<libc::uid_t>().ok())
+        // Not set for sessions that didn't go through a login
+        .filter(|&login| login != libc::uid_t::MAX)
+        .or_else(|| std::env::var("SUDO_UID").ok()?.parse().ok());
+    login.map(|login| login != uid).unwrap_or(false)
+}
+
+/// The name of the current user, if we can find out without the shell
+pub fn username() -> Option<String> {
     #[cfg(feature = "users")]
-    let uid = users::get_current_uid();
-    #[cfg(feature = "users")]
-    { if uid == 0 {
-        bg = p.theme.username_root_bg;
-        fg = p.theme.username_root_fg;
-    } }
+    { users::get_user_by_uid(users::get_current_uid())
+        .and_then(|user| user.name().to_str().map(String::from)) }
+    #[cfg(not(feature = "users"))]
+    { std::env::var("USER").ok() }
+}
 
-    p.segments.push(match p.shell {
-        Shell::Bare => Segment::new(
+/// `visible` is what `options.visible()` said, which the host module needs
+/// too
+pub fn segment_user(p: &mut Powerline, options: &UserHostOptions, visible: bool) {
+    if !visible {
+        return;
+    }
+
+    let (bg, fg) = if unsafe { libc::getuid() } == 0 {
+        (p.theme.username_root_bg, p.theme.username_root_fg)
+    } else {
+        (p.theme.username_bg, p.theme.username_fg)
+    };
+
+    if options.combined && (p.shell == Shell::Bare || segments::host_customized(&p.theme)) {
+        let user = username().unwrap_or_else(|| String::from("error"));
+        let (host, bg, fg) = match segments::hostname() {
+            // The host's own colors, like the host module would use, unless
+            // this is root which should stand out
+            Some(name) => {
+                let (host, host_bg, host_fg) = segments::host_style(&p.theme, &name);
+                if segments::host_customized(&p.theme) && unsafe { libc::getuid() } != 0 {
+                    (host, host_bg, host_fg)
+                } else {
+                    (host, bg, fg)
+                }
+            },
+            None => (String::from("error"), bg, fg)
+        };
+        p.segments.push(Segment::new(bg, fg, format!("{}@{}", user, host)).bold());
+        return;
+    }
+
+    p.segments.push(match (p.shell, options.combined) {
+        (Shell::Bare, _) => Segment::new(
             bg,
             fg,
-            {
-                #[cfg(not(feature = "users"))]
-                { Cow::from("error") }
-                #[cfg(feature = "users")]
-                { if let Some(user) = users::get_user_by_uid(uid) {
-                    if let Some(name) = user.name().to_str() {
-                        Cow::from(String::from(name))
-                    } else {
-                        Cow::from("error")
-                    }
-                } else {
-                    Cow::from("error")
-                } }
+            match username() {
+                Some(name) => Cow::from(name),
+                None => Cow::from("error")
             }
         ).bold(),
-        Shell::Bash => Segment::new(bg, fg, "\\u").dont_escape().bold(),
-        Shell::Zsh => Segment::new(bg, fg, "%n").dont_escape().bold(),
+        (Shell::Bash, false) => Segment::new(bg, fg, "\\u").dont_escape().bold(),
+        (Shell::Bash, true) => Segment::new(bg, fg, "\\u@\\h").dont_escape().bold(),
+        (Shell::Zsh, false) => Segment::new(bg, fg, "%n").dont_escape().bold(),
+        (Shell::Zsh, true) => Segment::new(bg, fg, "%n@%m").dont_escape().bold(),
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn switched() {
+        let proc = TempDir::new("user");
+        proc.write("self/loginuid", "1000");
+        assert!(!switched_user(&proc, 1000));
+        assert!(switched_user(&proc, 0));
+        assert!(switched_user(&proc, 1001));
+    }
+}
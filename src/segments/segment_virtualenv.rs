@@ -1,19 +1,115 @@
-use std::{env, path};
-use crate::{format, Powerline, Segment};
+use std::{env, fs, path::Path};
+use crate::{format, segments, Powerline, Segment};
+
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name().map(|name| name.to_string_lossy().into_owned())
+}
+
+/// The prompt someone gave with `python -m venv --prompt`, from pyvenv.cfg
+fn pyvenv_prompt(config: &str) -> Option<String> {
+    config.lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(2, '=');
+            if parts.next()?.trim() == "prompt" { parts.next() } else { None }
+        })
+        .map(|value| value.trim().trim_matches(|c| c == '"' || c == '\'').to_string())
+        .find(|prompt| !prompt.is_empty())
+}
+
+/// Strips what Poetry and pipenv add to the project name, like
+/// `myproj-AbC12_-x-py3.11`. The hash is 8 characters of URL safe base64.
+fn strip_hash(name: &str, managed: bool) -> &str {
+    let (name, poetry) = match name.rfind("-py") {
+        Some(i) if name[i + 3..].chars().all(|c| c.is_ascii_digit() || c == '.') && name.len() > i + 3 => (&name[..i], true),
+        _ => (name, false)
+    };
+    if !poetry && !managed {
+        return name;
+    }
+    let hash_start = match name.len().checked_sub(9) {
+        Some(i) if name.is_char_boundary(i) && name[i..].starts_with('-') => i,
+        _ => return name
+    };
+    let hash = &name[hash_start + 1..];
+    if hash_start > 0 && hash.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        &name[..hash_start]
+    } else {
+        name
+    }
+}
+
+fn venv_name(venv: &Path) -> Option<String> {
+    if let Some(prompt) = fs::read_to_string(venv.join("pyvenv.cfg")).ok().and_then(|config| pyvenv_prompt(&config)) {
+        return Some(prompt);
+    }
+    let name = file_name(venv)?;
+    match &*name {
+        // Generic names say nothing, but the project they're in does
+        ".venv" | "venv" | ".env" | "env" => venv.parent().and_then(file_name).or(Some(name)),
+        _ => {
+            // Both keep their venvs in a directory called virtualenvs
+            let managed = env::var("PIPENV_ACTIVE").map(|active| active == "1").unwrap_or(false)
+                || venv.parent().and_then(file_name).map(|parent| parent == "virtualenvs").unwrap_or(false);
+            Some(strip_hash(&name, managed).to_string())
+        }
+    }
+}
+
+fn pyenv_version() -> Option<String> {
+    let version = match env::var("PYENV_VERSION") {
+        Ok(version) => version,
+        Err(_) => fs::read_to_string(segments::find_in_ancestors(".python-version")?).ok()?
+    };
+    // Several versions can be active at once, the first one is used for python
+    version.split(|c: char| c == ':' || c.is_whitespace())
+        .find(|version| !version.is_empty())
+        .filter(|&version| version != "system")
+        .map(String::from)
+}
 
 pub fn segment_virtualenv(p: &mut Powerline) {
-    if let Ok(Some(virtual_env_name)) = env::var("VIRTUAL_ENV")
-        .or_else(|_| env::var("CONDA_ENV_PATH"))
-        .or_else(|_| env::var("CONDA_DEFAULT_ENV"))
-        .map(|env_path| {
-            path::Path::new(&env_path)
-                .file_name()
-                .and_then(|env_name| Some(env_name.to_string_lossy().into_owned()))
-        }) {
+    let name = if let Ok(venv) = env::var("VIRTUAL_ENV") {
+        venv_name(Path::new(&venv))
+    } else if let Ok(conda) = env::var("CONDA_ENV_PATH").or_else(|_| env::var("CONDA_DEFAULT_ENV")) {
+        file_name(Path::new(&conda))
+    } else {
+        pyenv_version()
+    };
+
+    if let Some(mut name) = name {
+        format::escape(p.shell, &mut name);
         p.segments.push(Segment::new(
             p.theme.virtual_env_bg,
             p.theme.virtual_env_fg,
-            format::as_bold(p.shell, &virtual_env_name),
+            format::as_bold(p.shell, &name),
         ).dont_escape());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt() {
+        let config = "home = /usr/bin\ninclude-system-site-packages = false\nprompt = 'my project'\n";
+        assert_eq!(pyvenv_prompt(config), Some(String::from("my project")));
+        assert_eq!(pyvenv_prompt("home = /usr/bin\nprompt =\n"), None);
+        assert_eq!(pyvenv_prompt("home = /usr/bin\n"), None);
+    }
+
+    #[test]
+    fn hashes() {
+        // Poetry
+        assert_eq!(strip_hash("myproj-AbC12_-x-py3.11", false), "myproj");
+        assert_eq!(strip_hash("my-proj-AbC12_-x-py3.11", false), "my-proj");
+        // pipenv, which has no python version
+        assert_eq!(strip_hash("myproj-AbC12_-x", true), "myproj");
+        assert_eq!(strip_hash("myproj-AbC12_-x", false), "myproj-AbC12_-x");
+        // Not a hash
+        assert_eq!(strip_hash("myproj-py3.11", false), "myproj");
+        assert_eq!(strip_hash("my-project", true), "my-project");
+        assert_eq!(strip_hash("-AbC12_-x", true), "-AbC12_-x");
+        assert_eq!(strip_hash("myproj-copy", true), "myproj-copy");
+    }
+}
@@ -0,0 +1,46 @@
+//! Helpers shared between the tests of different modules
+
+use std::{
+    env,
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering}
+};
+
+/// A fresh directory under the system's temporary directory. It's removed
+/// when dropped, so also when the test using it panics.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        // Tests run in parallel, so even the same test may want two
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let count = COUNT.fetch_add(1, Ordering::Relaxed);
+        let path = env::temp_dir().join(format!("powerline-rs-{}-{}-{}", name, process::id(), count));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+    /// Writes a file relative to the directory, creating any directories
+    /// in between
+    pub fn write<P: AsRef<Path>>(&self, file: P, content: &str) -> PathBuf {
+        let path = self.0.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+}
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -14,6 +14,13 @@ pub struct Theme {
     pub username_root_fg: u8,
     pub hostname_bg: u8,
     pub hostname_fg: u8,
+    /// Backgrounds to pick from based on a hash of the host name
+    pub hostname_palette: Vec<u8>,
+    /// Domain suffixes to remove from the host name, where ".*" removes the
+    /// whole domain
+    pub hostname_strip: Vec<String>,
+
+    pub hosts: Vec<HostEntry>,
 
     pub jobs_bg: u8,
     pub jobs_fg: u8,
@@ -21,6 +28,9 @@ pub struct Theme {
     pub time_bg: u8,
     pub time_fg: u8,
 
+    pub duration_bg: u8,
+    pub duration_fg: u8,
+
     pub ssh_bg: u8,
     pub ssh_fg: u8,
 
@@ -66,6 +76,11 @@ pub struct Theme {
     pub cmd_failed_bg: u8,
     pub cmd_failed_fg: u8,
 
+    pub prompt_user_char: char,
+    pub prompt_root_char: char,
+    pub prompt_user_failed_char: char,
+    pub prompt_root_failed_char: char,
+
     pub ps_bg: u8,
     pub ps_fg: u8,
 
@@ -74,6 +89,226 @@ pub struct Theme {
 
     pub nixshell_bg: u8,
     pub nixshell_fg: u8,
+
+    pub container_bg: u8,
+    pub container_fg: u8,
+
+    pub env_bg: u8,
+    pub env_fg: u8,
+
+    pub env: Vec<EnvEntry>,
+
+    pub custom_bg: u8,
+    pub custom_fg: u8,
+
+    pub custom: Vec<CustomEntry>,
+
+    pub plugin_bg: u8,
+    pub plugin_fg: u8,
+
+    pub plugins: Vec<PluginEntry>,
+
+    pub kube_bg: u8,
+    pub kube_fg: u8,
+
+    pub kube: Vec<KubeEntry>,
+
+    pub aws_bg: u8,
+    pub aws_fg: u8,
+    pub aws_expired_bg: u8,
+    pub aws_expired_fg: u8,
+    pub gcp_bg: u8,
+    pub gcp_fg: u8,
+    pub azure_bg: u8,
+    pub azure_fg: u8,
+
+    pub rust_bg: u8,
+    pub rust_fg: u8,
+    pub node_bg: u8,
+    pub node_fg: u8,
+    pub python_bg: u8,
+    pub python_fg: u8,
+    pub go_bg: u8,
+    pub go_fg: u8,
+
+    pub battery_bg: u8,
+    pub battery_fg: u8,
+    pub battery_warning_bg: u8,
+    pub battery_warning_fg: u8,
+    pub battery_critical_bg: u8,
+    pub battery_critical_fg: u8,
+    /// Percentages at or below which the warning and critical colors are
+    /// used, while discharging
+    pub battery_warning_level: u8,
+    pub battery_critical_level: u8,
+
+    pub battery_charging_char: char,
+    pub battery_discharging_char: char,
+
+    pub load_bg: u8,
+    pub load_fg: u8,
+    pub load_warning_bg: u8,
+    pub load_warning_fg: u8,
+    pub load_critical_bg: u8,
+    pub load_critical_fg: u8,
+
+    pub mem_bg: u8,
+    pub mem_fg: u8,
+    pub mem_warning_bg: u8,
+    pub mem_warning_fg: u8,
+    pub mem_critical_bg: u8,
+    pub mem_critical_fg: u8,
+
+    pub disk_bg: u8,
+    pub disk_fg: u8,
+    pub disk_warning_bg: u8,
+    pub disk_warning_fg: u8,
+    pub disk_critical_bg: u8,
+    pub disk_critical_fg: u8,
+    /// Percentages at or above which the warning and critical colors are
+    /// used. For load, that's of the CPU count, so 100 means every CPU is
+    /// busy.
+    pub load_warning_level: u8,
+    pub load_critical_level: u8,
+    pub mem_warning_level: u8,
+    pub mem_critical_level: u8,
+    pub disk_warning_level: u8,
+    pub disk_critical_level: u8,
+}
+
+/// One environment variable shown by the env module, configured with
+/// `env.<id>.<key> = <value>` lines in the theme.
+#[derive(Clone)]
+pub struct EnvEntry {
+    pub id: String,
+    /// The variable to read, defaults to the id
+    pub var: Option<String>,
+    pub label: Option<String>,
+    pub bg: Option<u8>,
+    pub fg: Option<u8>,
+    /// Per-value color overrides, from `env.<id>.bg.<value>`
+    pub value_bg: Vec<(String, u8)>,
+    pub value_fg: Vec<(String, u8)>,
+    /// Only show the segment if the value matches one of these comma
+    /// separated patterns. Otherwise it's shown whenever it's set.
+    pub show_if: Option<String>,
+}
+impl EnvEntry {
+    pub fn new(id: String) -> Self {
+        EnvEntry {
+            id,
+            var: None,
+            label: None,
+            bg: None,
+            fg: None,
+            value_bg: Vec::new(),
+            value_fg: Vec::new(),
+            show_if: None,
+        }
+    }
+    pub fn var(&self) -> &str {
+        self.var.as_ref().unwrap_or(&self.id)
+    }
+}
+
+/// A command whose output is shown by the custom module, configured with
+/// `custom.<id>.<key> = <value>` lines in the theme.
+#[derive(Clone)]
+pub struct CustomEntry {
+    pub id: String,
+    pub command: Option<String>,
+    /// Milliseconds to wait for the command before hiding the segment
+    pub timeout: Option<u64>,
+    pub bg: Option<u8>,
+    pub fg: Option<u8>,
+    /// Only run the command if this file exists in the current directory
+    /// or any of its parents
+    pub if_exists: Option<String>,
+}
+impl CustomEntry {
+    pub fn new(id: String) -> Self {
+        CustomEntry {
+            id,
+            command: None,
+            timeout: None,
+            bg: None,
+            fg: None,
+            if_exists: None,
+        }
+    }
+}
+
+/// An external segment provider, configured with `plugin.<id>.<key> =
+/// <value>` lines in the theme.
+#[derive(Clone)]
+pub struct PluginEntry {
+    pub id: String,
+    pub command: Option<String>,
+    /// Milliseconds to wait for the plugin before skipping it
+    pub timeout: Option<u64>,
+}
+impl PluginEntry {
+    pub fn new(id: String) -> Self {
+        PluginEntry {
+            id,
+            command: None,
+            timeout: None,
+        }
+    }
+}
+
+/// Overrides for kubernetes contexts matching a pattern, configured with
+/// `kube.<id>.<key> = <value>` lines in the theme.
+#[derive(Clone)]
+pub struct KubeEntry {
+    pub id: String,
+    /// The contexts to match, defaults to the id
+    pub context: Option<String>,
+    /// Shown instead of the context name
+    pub alias: Option<String>,
+    pub bg: Option<u8>,
+    pub fg: Option<u8>,
+}
+impl KubeEntry {
+    pub fn new(id: String) -> Self {
+        KubeEntry {
+            id,
+            context: None,
+            alias: None,
+            bg: None,
+            fg: None,
+        }
+    }
+    pub fn context(&self) -> &str {
+        self.context.as_ref().unwrap_or(&self.id)
+    }
+}
+
+/// Overrides for hosts matching a pattern, configured with
+/// `host.<id>.<key> = <value>` lines in the theme.
+#[derive(Clone)]
+pub struct HostEntry {
+    pub id: String,
+    /// The full host names to match, defaults to the id
+    pub name: Option<String>,
+    /// Shown instead of the host name
+    pub alias: Option<String>,
+    pub bg: Option<u8>,
+    pub fg: Option<u8>,
+}
+impl HostEntry {
+    pub fn new(id: String) -> Self {
+        HostEntry {
+            id,
+            name: None,
+            alias: None,
+            bg: None,
+            fg: None,
+        }
+    }
+    pub fn name(&self) -> &str {
+        self.name.as_ref().unwrap_or(&self.id)
+    }
 }
 
 pub const DEFAULT: Theme = Theme {
@@ -91,6 +326,10 @@ pub const DEFAULT: Theme = Theme {
     username_root_fg: 231,
     hostname_bg: 24,
     hostname_fg: 231,
+    hostname_palette: Vec::new(),
+    hostname_strip: Vec::new(),
+
+    hosts: Vec::new(),
 
     jobs_bg: 238,
     jobs_fg: 39,
@@ -100,6 +339,9 @@ pub const DEFAULT: Theme = Theme {
     time_bg: 238,
     time_fg: 250,
 
+    duration_bg: 238,
+    duration_fg: 250,
+
     ssh_bg: 166,
     ssh_fg: 254,
 
@@ -145,17 +387,102 @@ pub const DEFAULT: Theme = Theme {
     cmd_failed_bg: 124,
     cmd_failed_fg: 231,
 
+    prompt_user_char: '$',
+    prompt_root_char: '#',
+    prompt_user_failed_char: '$',
+    prompt_root_failed_char: '#',
+
     virtual_env_bg: 233,
     virtual_env_fg: 162,
 
     nixshell_bg: 237,
     nixshell_fg: 130,
+
+    container_bg: 25,
+    container_fg: 231,
+
+    env_bg: 238,
+    env_fg: 250,
+
+    env: Vec::new(),
+
+    custom_bg: 238,
+    custom_fg: 250,
+
+    custom: Vec::new(),
+
+    plugin_bg: 238,
+    plugin_fg: 250,
+
+    plugins: Vec::new(),
+
+    kube_bg: 26,
+    kube_fg: 231,
+
+    kube: Vec::new(),
+
+    aws_bg: 208,
+    aws_fg: 16,
+    aws_expired_bg: 124,
+    aws_expired_fg: 231,
+    gcp_bg: 33,
+    gcp_fg: 231,
+    azure_bg: 25,
+    azure_fg: 231,
+
+    rust_bg: 94,
+    rust_fg: 231,
+    node_bg: 28,
+    node_fg: 231,
+    python_bg: 25,
+    python_fg: 220,
+    go_bg: 37,
+    go_fg: 231,
+
+    battery_bg: 238,
+    battery_fg: 250,
+    battery_warning_bg: 172,
+    battery_warning_fg: 231,
+    battery_critical_bg: 124,
+    battery_critical_fg: 231,
+    battery_warning_level: 30,
+    battery_critical_level: 10,
+
+    battery_charging_char: '⚡',
+    battery_discharging_char: '⬇',
+
+    load_bg: 238,
+    load_fg: 250,
+    load_warning_bg: 172,
+    load_warning_fg: 231,
+    load_critical_bg: 124,
+    load_critical_fg: 231,
+    load_warning_level: 70,
+    load_critical_level: 100,
+
+    mem_bg: 238,
+    mem_fg: 250,
+    mem_warning_bg: 172,
+    mem_warning_fg: 231,
+    mem_critical_bg: 124,
+    mem_critical_fg: 231,
+    mem_warning_level: 80,
+    mem_critical_level: 95,
+
+    disk_bg: 238,
+    disk_fg: 250,
+    disk_warning_bg: 172,
+    disk_warning_fg: 231,
+    disk_critical_bg: 124,
+    disk_critical_fg: 231,
+    disk_warning_level: 80,
+    disk_critical_level: 95,
 };
 
 use std::error::Error as StdError;
 use std::fmt;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Write};
 
 #[derive(Debug)]
 pub struct ErrCorrupt;
@@ -180,20 +507,39 @@ pub fn load(file: &str) -> Result<Theme, Box<dyn StdError>> {
         }
         let mut parts = line.splitn(2, '=');
 
-        let variable = parts.next().map(|inner| inner.trim()).ok_or_else(|| ErrCorrupt)?;
-        let value    = parts.next().map(|inner| inner.trim()).ok_or_else(|| ErrCorrupt)?;
-
-        if variable.ends_with("char") {
-            let index = theme_index_char(&mut theme, variable).ok_or_else(|| ErrCorrupt)?;
+        let variable = parts.next().map(|inner| inner.trim()).ok_or(ErrCorrupt)?;
+        let value    = parts.next().map(|inner| inner.trim()).ok_or(ErrCorrupt)?;
+
+        if let Some(key) = variable.strip_prefix("env.") {
+            load_env(&mut theme.env, key, value)?;
+        } else if let Some(key) = variable.strip_prefix("custom.") {
+            load_custom(&mut theme.custom, key, value)?;
+        } else if let Some(key) = variable.strip_prefix("plugin.") {
+            load_plugin(&mut theme.plugins, key, value)?;
+        } else if let Some(key) = variable.strip_prefix("kube.") {
+            load_kube(&mut theme.kube, key, value)?;
+        } else if let Some(key) = variable.strip_prefix("host.") {
+            load_host(&mut theme.hosts, key, value)?;
+        } else if variable == "hostname_palette" {
+            theme.hostname_palette = value.split(',')
+                .map(|color| color.trim().parse())
+                .collect::<Result<_, _>>()?;
+        } else if variable == "hostname_strip" {
+            theme.hostname_strip = value.split(',')
+                .map(|suffix| suffix.trim().to_string())
+                .filter(|suffix| !suffix.is_empty())
+                .collect();
+        } else if variable.ends_with("char") {
+            let index = theme_index_char(&mut theme, variable).ok_or(ErrCorrupt)?;
 
             if value.chars().count() == 1 {
                 *index = value.parse()?;
             } else {
                 let codepoint = u32::from_str_radix(value, 16)?;
-                *index = std::char::from_u32(codepoint).ok_or_else(|| ErrCorrupt)?;
+                *index = std::char::from_u32(codepoint).ok_or(ErrCorrupt)?;
             }
         } else {
-            let index = theme_index_u8(&mut theme, variable).ok_or_else(|| ErrCorrupt)?;
+            let index = theme_index_u8(&mut theme, variable).ok_or(ErrCorrupt)?;
             *index = value.parse()?;
         }
     }
@@ -201,84 +547,428 @@ pub fn load(file: &str) -> Result<Theme, Box<dyn StdError>> {
     Ok(theme)
 }
 
-fn theme_index_u8<'a>(theme: &'a mut Theme, name: &str) -> Option<&'a mut u8> {
-    match name {
-        "separator_fg" => Some(&mut theme.separator_fg),
-
-        "home_bg" => Some(&mut theme.home_bg),
-        "home_fg" => Some(&mut theme.home_fg),
-        "path_bg" => Some(&mut theme.path_bg),
-        "path_fg" => Some(&mut theme.path_fg),
-        "cwd_fg"  => Some(&mut theme.cwd_fg),
-
-        "username_bg" => Some(&mut theme.username_bg),
-        "username_fg" => Some(&mut theme.username_fg),
-        "username_root_bg" => Some(&mut theme.username_root_bg),
-        "username_root_fg" => Some(&mut theme.username_root_fg),
-        "hostname_bg" => Some(&mut theme.hostname_bg),
-        "hostname_fg" => Some(&mut theme.hostname_fg),
-
-        "jobs_bg" => Some(&mut theme.jobs_bg),
-        "jobs_fg" => Some(&mut theme.jobs_fg),
-
-        "time_bg" => Some(&mut theme.time_bg),
-        "time_fg" => Some(&mut theme.time_fg),
-
-        "ssh_bg" => Some(&mut theme.ssh_bg),
-        "ssh_fg" => Some(&mut theme.ssh_fg),
-
-        "ro_bg" => Some(&mut theme.ro_bg),
-        "ro_fg" => Some(&mut theme.ro_fg),
-
-        "git_clean_bg" => Some(&mut theme.git_clean_bg),
-        "git_clean_fg" => Some(&mut theme.git_clean_fg),
-        "git_dirty_bg" => Some(&mut theme.git_dirty_bg),
-        "git_dirty_fg" => Some(&mut theme.git_dirty_fg),
-        "git_detached_bg" => Some(&mut theme.git_detached_bg),
-        "git_detached_fg" => Some(&mut theme.git_detached_fg),
-        "git_ahead_bg" => Some(&mut theme.git_ahead_bg),
-        "git_ahead_fg" => Some(&mut theme.git_ahead_fg),
-        "git_behind_bg" => Some(&mut theme.git_behind_bg),
-        "git_behind_fg" => Some(&mut theme.git_behind_fg),
-        "git_conflicted_bg" => Some(&mut theme.git_conflicted_bg),
-        "git_conflicted_fg" => Some(&mut theme.git_conflicted_fg),
-        "git_changed_bg" => Some(&mut theme.git_changed_bg),
-        "git_changed_fg" => Some(&mut theme.git_changed_fg),
-        "git_staged_bg" => Some(&mut theme.git_staged_bg),
-        "git_staged_fg" => Some(&mut theme.git_staged_fg),
-        "git_untracked_bg" => Some(&mut theme.git_untracked_bg),
-        "git_untracked_fg" => Some(&mut theme.git_untracked_fg),
-        "git_stashed_bg" => Some(&mut theme.git_stashed_bg),
-        "git_stashed_fg" => Some(&mut theme.git_stashed_fg),
-
-        "cmd_passed_bg" => Some(&mut theme.cmd_passed_bg),
-        "cmd_passed_fg" => Some(&mut theme.cmd_passed_fg),
-        "cmd_failed_bg" => Some(&mut theme.cmd_failed_bg),
-        "cmd_failed_fg" => Some(&mut theme.cmd_failed_fg),
-
-        "virtual_env_bg" => Some(&mut theme.virtual_env_bg),
-        "virtual_env_fg" => Some(&mut theme.virtual_env_fg),
-
-        "nixshell_bg" => Some(&mut theme.nixshell_bg),
-        "nixshell_fg" => Some(&mut theme.nixshell_fg),
-
-        _ => None
+/// Finds the entry with the specified id, or adds a new one to the end
+fn entry_by_id<'a, T>(entries: &'a mut Vec<T>, id: &str, get_id: fn(&T) -> &str, new: fn(String) -> T) -> &'a mut T {
+    match entries.iter().position(|entry| get_id(entry) == id) {
+        Some(i) => &mut entries[i],
+        None => {
+            entries.push(new(id.to_string()));
+            entries.last_mut().unwrap()
+        }
+    }
+}
+
+fn load_env(entries: &mut Vec<EnvEntry>, key: &str, value: &str) -> Result<(), Box<dyn StdError>> {
+    let mut parts = key.splitn(3, '.');
+    let id    = parts.next().ok_or(ErrCorrupt)?;
+    let field = parts.next().ok_or(ErrCorrupt)?;
+
+    let entry = entry_by_id(entries, id, |entry| &entry.id, EnvEntry::new);
+
+    match (field, parts.next()) {
+        ("var", None)      => entry.var = Some(value.to_string()),
+        ("label", None)    => entry.label = Some(value.to_string()),
+        ("bg", None)       => entry.bg = Some(value.parse()?),
+        ("fg", None)       => entry.fg = Some(value.parse()?),
+        ("bg", Some(when)) => entry.value_bg.push((when.to_string(), value.parse()?)),
+        ("fg", Some(when)) => entry.value_fg.push((when.to_string(), value.parse()?)),
+        ("show_if", None)  => entry.show_if = Some(value.to_string()),
+        _ => return Err(Box::new(ErrCorrupt))
+    }
+    Ok(())
+}
+
+fn load_custom(entries: &mut Vec<CustomEntry>, key: &str, value: &str) -> Result<(), Box<dyn StdError>> {
+    let mut parts = key.splitn(2, '.');
+    let id    = parts.next().ok_or(ErrCorrupt)?;
+    let field = parts.next().ok_or(ErrCorrupt)?;
+
+    let entry = entry_by_id(entries, id, |entry| &entry.id, CustomEntry::new);
+
+    match field {
+        "command"   => entry.command = Some(value.to_string()),
+        "timeout"   => entry.timeout = Some(value.parse()?),
+        "bg"        => entry.bg = Some(value.parse()?),
+        "fg"        => entry.fg = Some(value.parse()?),
+        "if_exists" => entry.if_exists = Some(value.to_string()),
+        _ => return Err(Box::new(ErrCorrupt))
+    }
+    Ok(())
+}
+
+fn load_plugin(entries: &mut Vec<PluginEntry>, key: &str, value: &str) -> Result<(), Box<dyn StdError>> {
+    let mut parts = key.splitn(2, '.');
+    let id    = parts.next().ok_or(ErrCorrupt)?;
+    let field = parts.next().ok_or(ErrCorrupt)?;
+
+    let entry = entry_by_id(entries, id, |entry| &entry.id, PluginEntry::new);
+
+    match field {
+        "command" => entry.command = Some(value.to_string()),
+        "timeout" => entry.timeout = Some(value.parse()?),
+        _ => return Err(Box::new(ErrCorrupt))
+    }
+    Ok(())
+}
+
+fn load_kube(entries: &mut Vec<KubeEntry>, key: &str, value: &str) -> Result<(), Box<dyn StdError>> {
+    let mut parts = key.splitn(2, '.');
+    let id    = parts.next().ok_or(ErrCorrupt)?;
+    let field = parts.next().ok_or(ErrCorrupt)?;
+
+    let entry = entry_by_id(entries, id, |entry| &entry.id, KubeEntry::new);
+
+    match field {
+        "context" => entry.context = Some(value.to_string()),
+        "alias"   => entry.alias = Some(value.to_string()),
+        "bg"      => entry.bg = Some(value.parse()?),
+        "fg"      => entry.fg = Some(value.parse()?),
+        _ => return Err(Box::new(ErrCorrupt))
+    }
+    Ok(())
+}
+
+fn load_host(entries: &mut Vec<HostEntry>, key: &str, value: &str) -> Result<(), Box<dyn StdError>> {
+    let mut parts = key.splitn(2, '.');
+    let id    = parts.next().ok_or(ErrCorrupt)?;
+    let field = parts.next().ok_or(ErrCorrupt)?;
+
+    let entry = entry_by_id(entries, id, |entry| &entry.id, HostEntry::new);
+
+    match field {
+        "name"  => entry.name = Some(value.to_string()),
+        "alias" => entry.alias = Some(value.to_string()),
+        "bg"    => entry.bg = Some(value.parse()?),
+        "fg"    => entry.fg = Some(value.parse()?),
+        _ => return Err(Box::new(ErrCorrupt))
+    }
+    Ok(())
+}
+
+pub fn dump<W: Write>(theme: &Theme, out: &mut W) -> io::Result<()> {
+    // theme_index_* wants a mutable theme
+    let mut theme = theme.clone();
+
+    for name in U8_KEYS {
+        writeln!(out, "{} = {}", name, theme_index_u8(&mut theme, name).unwrap())?;
+    }
+    for name in CHAR_KEYS {
+        let c = *theme_index_char(&mut theme, name).unwrap();
+        if c.is_whitespace() || c.is_control() {
+            // Would get trimmed away when loading
+            writeln!(out, "{} = {:04x}", name, c as u32)?;
+        } else {
+            writeln!(out, "{} = {}", name, c)?;
+        }
+    }
+    if !theme.hostname_palette.is_empty() {
+        let palette: Vec<String> = theme.hostname_palette.iter().map(u8::to_string).collect();
+        writeln!(out, "hostname_palette = {}", palette.join(", "))?;
+    }
+    if !theme.hostname_strip.is_empty() {
+        writeln!(out, "hostname_strip = {}", theme.hostname_strip.join(", "))?;
+    }
+    for entry in &theme.env {
+        let prefix = format!("env.{}", entry.id);
+        if let Some(ref var) = entry.var {
+            writeln!(out, "{}.var = {}", prefix, var)?;
+        }
+        if let Some(ref label) = entry.label {
+            writeln!(out, "{}.label = {}", prefix, label)?;
+        }
+        if let Some(bg) = entry.bg {
+            writeln!(out, "{}.bg = {}", prefix, bg)?;
+        }
+        if let Some(fg) = entry.fg {
+            writeln!(out, "{}.fg = {}", prefix, fg)?;
+        }
+        for (when, bg) in &entry.value_bg {
+            writeln!(out, "{}.bg.{} = {}", prefix, when, bg)?;
+        }
+        for (when, fg) in &entry.value_fg {
+            writeln!(out, "{}.fg.{} = {}", prefix, when, fg)?;
+        }
+        if let Some(ref show_if) = entry.show_if {
+            writeln!(out, "{}.show_if = {}", prefix, show_if)?;
+        }
+    }
+    for entry in &theme.custom {
+        let prefix = format!("custom.{}", entry.id);
+        if let Some(ref command) = entry.command {
+            writeln!(out, "{}.command = {}", prefix, command)?;
+        }
+        if let Some(timeout) = entry.timeout {
+            writeln!(out, "{}.timeout = {}", prefix, timeout)?;
+        }
+        if let Some(bg) = entry.bg {
+            writeln!(out, "{}.bg = {}", prefix, bg)?;
+        }
+        if let Some(fg) = entry.fg {
+            writeln!(out, "{}.fg = {}", prefix, fg)?;
+        }
+        if let Some(ref if_exists) = entry.if_exists {
+            writeln!(out, "{}.if_exists = {}", prefix, if_exists)?;
+        }
+    }
+    for entry in &theme.plugins {
+        let prefix = format!("plugin.{}", entry.id);
+        if let Some(ref command) = entry.command {
+            writeln!(out, "{}.command = {}", prefix, command)?;
+        }
+        if let Some(timeout) = entry.timeout {
+            writeln!(out, "{}.timeout = {}", prefix, timeout)?;
+        }
+    }
+    for entry in &theme.kube {
+        let prefix = format!("kube.{}", entry.id);
+        if let Some(ref context) = entry.context {
+            writeln!(out, "{}.context = {}", prefix, context)?;
+        }
+        if let Some(ref alias) = entry.alias {
+            writeln!(out, "{}.alias = {}", prefix, alias)?;
+        }
+        if let Some(bg) = entry.bg {
+            writeln!(out, "{}.bg = {}", prefix, bg)?;
+        }
+        if let Some(fg) = entry.fg {
+            writeln!(out, "{}.fg = {}", prefix, fg)?;
+        }
+    }
+
+    for entry in &theme.hosts {
+        let prefix = format!("host.{}", entry.id);
+        if let Some(ref name) = entry.name {
+            writeln!(out, "{}.name = {}", prefix, name)?;
+        }
+        if let Some(ref alias) = entry.alias {
+            writeln!(out, "{}.alias = {}", prefix, alias)?;
+        }
+        if let Some(bg) = entry.bg {
+            writeln!(out, "{}.bg = {}", prefix, bg)?;
+        }
+        if let Some(fg) = entry.fg {
+            writeln!(out, "{}.fg = {}", prefix, fg)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Generates the list of plain keys used by `dump` together with the lookup
+/// functions used by `load`, so the two can't get out of sync
+macro_rules! theme_keys {
+    (u8 { $($u8:ident,)* } char { $($char:ident,)* }) => {
+        const U8_KEYS: &[&str] = &[$(stringify!($u8)),*];
+        const CHAR_KEYS: &[&str] = &[$(stringify!($char)),*];
+
+        fn theme_index_u8<'a>(theme: &'a mut Theme, name: &str) -> Option<&'a mut u8> {
+            match name {
+                $(stringify!($u8) => Some(&mut theme.$u8),)*
+                _ => None
+            }
+        }
+
+        fn theme_index_char<'a>(theme: &'a mut Theme, name: &str) -> Option<&'a mut char> {
+            match name {
+                $(stringify!($char) => Some(&mut theme.$char),)*
+                _ => None
+            }
+        }
+    };
+}
+
+theme_keys! {
+    u8 {
+        separator_fg,
+
+        home_bg,
+        home_fg,
+        path_bg,
+        path_fg,
+        cwd_fg,
+
+        username_bg,
+        username_fg,
+        username_root_bg,
+        username_root_fg,
+        hostname_bg,
+        hostname_fg,
+
+        jobs_bg,
+        jobs_fg,
+
+        time_bg,
+        time_fg,
+
+        duration_bg,
+        duration_fg,
+
+        ssh_bg,
+        ssh_fg,
+
+        ro_bg,
+        ro_fg,
+
+        git_clean_bg,
+        git_clean_fg,
+        git_dirty_bg,
+        git_dirty_fg,
+        git_detached_bg,
+        git_detached_fg,
+        git_ahead_bg,
+        git_ahead_fg,
+        git_behind_bg,
+        git_behind_fg,
+        git_conflicted_bg,
+        git_conflicted_fg,
+        git_changed_bg,
+        git_changed_fg,
+        git_staged_bg,
+        git_staged_fg,
+        git_untracked_bg,
+        git_untracked_fg,
+        git_stashed_bg,
+        git_stashed_fg,
+
+        cmd_passed_bg,
+        cmd_passed_fg,
+        cmd_failed_bg,
+        cmd_failed_fg,
+
+        ps_bg,
+        ps_fg,
+
+        virtual_env_bg,
+        virtual_env_fg,
+
+        nixshell_bg,
+        nixshell_fg,
+
+        container_bg,
+        container_fg,
+
+        env_bg,
+        env_fg,
+
+        custom_bg,
+        custom_fg,
+
+        plugin_bg,
+        plugin_fg,
+
+        kube_bg,
+        kube_fg,
+
+        aws_bg,
+        aws_fg,
+        aws_expired_bg,
+        aws_expired_fg,
+        gcp_bg,
+        gcp_fg,
+        azure_bg,
+        azure_fg,
+
+        rust_bg,
+        rust_fg,
+        node_bg,
+        node_fg,
+        python_bg,
+        python_fg,
+        go_bg,
+        go_fg,
+
+        battery_bg,
+        battery_fg,
+        battery_warning_bg,
+        battery_warning_fg,
+        battery_critical_bg,
+        battery_critical_fg,
+        battery_warning_level,
+        battery_critical_level,
+
+        load_bg,
+        load_fg,
+        load_warning_bg,
+        load_warning_fg,
+        load_critical_bg,
+        load_critical_fg,
+        load_warning_level,
+        load_critical_level,
+
+        mem_bg,
+        mem_fg,
+        mem_warning_bg,
+        mem_warning_fg,
+        mem_critical_bg,
+        mem_critical_fg,
+        mem_warning_level,
+        mem_critical_level,
+
+        disk_bg,
+        disk_fg,
+        disk_warning_bg,
+        disk_warning_fg,
+        disk_critical_bg,
+        disk_critical_fg,
+        disk_warning_level,
+        disk_critical_level,
+    }
+    char {
+        ssh_char,
+        ro_char,
+
+        git_ahead_char,
+        git_behind_char,
+        git_staged_char,
+        git_changed_char,
+        git_untracked_char,
+        git_conflicted_char,
+        git_stashed_char,
+
+        prompt_user_char,
+        prompt_root_char,
+        prompt_user_failed_char,
+        prompt_root_failed_char,
+
+        battery_charging_char,
+        battery_discharging_char,
     }
 }
 
-fn theme_index_char<'a>(theme: &'a mut Theme, name: &str) -> Option<&'a mut char> {
-    match name {
-        "ssh_char" => Some(&mut theme.ssh_char),
-        "ro_char" => Some(&mut theme.ro_char),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
 
-        "git_ahead_char" => Some(&mut theme.git_ahead_char),
-        "git_behind_char" => Some(&mut theme.git_behind_char),
-        "git_staged_char" => Some(&mut theme.git_staged_char),
-        "git_changed_char" => Some(&mut theme.git_changed_char),
-        "git_untracked_char" => Some(&mut theme.git_untracked_char),
-        "git_conflicted_char" => Some(&mut theme.git_conflicted_char),
-        "git_stashed_char" => Some(&mut theme.git_stashed_char),
+    fn dump_string(theme: &Theme) -> String {
+        let mut out = Vec::new();
+        dump(theme, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
 
-        _ => None
+    #[test]
+    fn dump_round_trip() {
+        let mut theme = DEFAULT.clone();
+        theme.ssh_char = '\t';
+        theme.ro_char = ' ';
+        theme.git_stashed_char = '9';
+        theme.hostname_palette = vec![1, 2, 3];
+        theme.hostname_strip = vec![String::from(".example.com")];
+        let mut host = HostEntry::new(String::from("work"));
+        host.alias = Some(String::from("w"));
+        host.bg = Some(22);
+        theme.hosts.push(host);
+
+        let dumped = dump_string(&theme);
+        assert!(dumped.contains("ssh_char = 0009\n"));
+
+        let dir = TempDir::new("theme");
+        let path = dir.write("dumped", &dumped);
+        let loaded = load(path.to_str().unwrap()).unwrap();
+
+        assert_eq!(loaded.ssh_char, '\t');
+        assert_eq!(loaded.ro_char, ' ');
+        assert_eq!(loaded.git_stashed_char, '9');
+        assert_eq!(dump_string(&loaded), dumped);
     }
 }
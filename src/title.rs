@@ -0,0 +1,35 @@
+use std::env;
+use crate::{format, segments, Powerline};
+
+/// Expands a title template like "{user}@{host}: {cwd}" and wraps it in the
+/// sequence that sets the terminal window title. Returns None for terminals
+/// that don't have a title.
+pub fn title(p: &mut Powerline, template: &str) -> Option<String> {
+    match env::var("TERM") {
+        Ok(ref term) if term == "linux" || term == "dumb" => return None,
+        _ => ()
+    }
+
+    let mut title = template.to_string();
+    if title.contains("{user}") {
+        title = title.replace("{user}", &segments::username().unwrap_or_default());
+    }
+    if title.contains("{host}") {
+        title = title.replace("{host}", &segments::hostname().unwrap_or_default());
+    }
+    if title.contains("{cwd}") {
+        title = title.replace("{cwd}", &segments::cwd_display());
+    }
+    if title.contains("{branch}") {
+        #[cfg(feature = "git2")]
+        let branch = segments::branch_name(p).unwrap_or_default();
+        #[cfg(not(feature = "git2"))]
+        let branch = String::new();
+        title = title.replace("{branch}", &branch);
+    }
+
+    // A stray BEL or ESC would end the sequence early
+    title.retain(|c| !c.is_control());
+    format::escape(p.shell, &mut title);
+    Some(format::osc(p.shell, &format!("0;{}", title)))
+}